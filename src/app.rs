@@ -1,17 +1,35 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 use chrono::Utc;
 use owo_colors::{OwoColorize, Stream};
 use serde::{Deserialize, Serialize};
+use similar::TextDiff;
 use tracing::info;
 
-use crate::diff::{RepoSettingsDiff, diff_labels, diff_repo_settings};
-use crate::error::Result;
-use crate::github::{GithubClient, LabelUsageEntry};
+use crate::config::StaleBranchAction;
+use crate::diff::{
+    AccessDiff, AccessKind, AutolinkDiff, DiscussionsDiff, EnvironmentChange, MilestoneDiff,
+    PropertyChange, RepoSettingsDiff, SettingCategory, SettingChange, TeamsDiff, WebhookDiff,
+    diff_access, diff_autolinks, diff_discussions, diff_environments, diff_labels, diff_milestones,
+    diff_org_settings, diff_properties, diff_repo_settings, diff_teams, diff_webhooks,
+};
+use crate::error::{Error, Result};
+use crate::forms::template_summary;
+use crate::github::{FileChange, GithubClient, LabelUsageEntry};
+use crate::journal::{FileBefore, Journal, JournalEntry};
 use crate::merge::{MergedRepoConfig, merge_sets_for_repo};
-use crate::sets::{IssueTemplateFile, LabelSpec, SetDefinition};
-use crate::settings::BranchProtectionRule;
+use crate::report::{
+    BlockedLabel, OrgReport, RepoReport, ReportSection, render_junit, render_markdown, render_sarif,
+};
+use crate::sets::{
+    IssueTemplateFile, LabelSpec, MilestoneSpec, MilestoneState, SetDefinition, TEMPLATE_DIRS,
+};
+use crate::settings::{
+    BranchProtectionRule, DiscussionCategorySpec, FileDelivery, OrgSettings, SecuritySettings,
+    TeamRole, TeamSpec, TeamsConfig, WebhookSpec,
+};
+use crate::yaml::{update_top_level_keys, yaml_equivalent};
 
 #[derive(Clone, Copy, Debug)]
 pub enum Mode {
@@ -20,267 +38,1346 @@ pub enum Mode {
 }
 
 const PR_BRANCH_PREFIX: &str = "gh-governor/updates-";
+/// Subject of the commits gh-governor writes; other commits on its branches came from people.
+const COMMIT_SUBJECT: &str = "Update .github files via gh-governor";
+const ISSUE_CONFIG_PATH: &str = ".github/ISSUE_TEMPLATE/config.yml";
+/// Locations GitHub reads CODEOWNERS from.
+const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Command-line options for `plan` and `apply`.
+#[derive(Clone, Debug, Default)]
+pub struct RunOptions {
+    /// Limit to these repositories; empty means all configured repos. Org settings and teams
+    /// are only handled when this is empty.
+    pub only_repos: Vec<String>,
+    pub verbose: bool,
+    /// Delete teams that are not declared in the teams file.
+    pub prune_teams: bool,
+    /// Print a unified diff for each `.github` file that plan would add or update.
+    pub show_diff: bool,
+    /// Lines of diff printed per file before truncating; 0 prints everything.
+    pub diff_max_lines: usize,
+    /// Output format for plan results.
+    pub report: ReportFormat,
+    /// Record failed steps and carry on with independent steps, org settings, teams and the
+    /// remaining repositories, then print a summary.
+    pub keep_going: bool,
+    /// Where apply records its mutations; defaults to a timestamped file under
+    /// `.gh-governor/` next to the root config.
+    pub journal: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Colored per-repo sections.
+    #[default]
+    Text,
+    /// A summary table plus collapsible per-repo details, for PR comments.
+    Markdown,
+    /// JUnit XML with a test case per repository and policy.
+    Junit,
+    /// SARIF 2.1.0 with a result per failing repository and policy.
+    Sarif,
+}
 
 pub async fn run(
     mode: Mode,
     root: crate::config::RootConfig,
     root_path: PathBuf,
     sets_dir: PathBuf,
-    only_repos: Vec<String>,
     gh: GithubClient,
-    verbose: bool,
+    options: RunOptions,
 ) -> Result<()> {
-    let merged = prepare_merged(&root, &sets_dir, &only_repos)?;
+    if options.report != ReportFormat::Text {
+        owo_colors::set_override(false);
+    }
+    let mut root = root;
+    if !root.repo_selectors.is_empty() {
+        let org_values = gh.list_org_property_values().await?;
+        root.apply_repo_selectors(&org_values);
+    }
+    let merged = prepare_merged(&root, &sets_dir, &options.only_repos, options.keep_going)?;
     info!(
         "loaded config for org '{}' from {}",
         root.org,
         root_path.display()
     );
 
-    handle_repos(mode, &gh, merged, verbose).await
+    let config_base = root_path.parent().unwrap_or(Path::new("."));
+    // `--repo` narrows the run to repositories, so org settings and teams are left out.
+    let org_wide = options.only_repos.is_empty();
+    let mut journal = match mode {
+        Mode::Apply => {
+            let path = options.journal.clone().unwrap_or_else(|| {
+                config_base.join(".gh-governor").join(format!(
+                    "journal-{}.jsonl",
+                    Utc::now().format("%Y%m%dT%H%M%S")
+                ))
+            });
+            Journal::create(&path, &root.org)?
+        }
+        Mode::Plan => Journal::default(),
+    };
+    let mut org_report = OrgReport::default();
+    let mut outcomes = Vec::new();
+    if org_wide && let Some(org_settings) = crate::config::load_org_settings(config_base)? {
+        let mut failures = Vec::new();
+        let result = handle_org(
+            mode,
+            &gh,
+            &root.org,
+            &org_settings,
+            options.report,
+            &mut journal,
+        )
+        .await;
+        if let Some(section) = settle(
+            "updating org settings",
+            result,
+            &mut failures,
+            options.keep_going,
+        )? {
+            org_report.sections.push(section);
+        }
+        for (step, err) in &failures {
+            eprintln!("Org {}: failed while {step}: {err}", root.org);
+        }
+        outcomes.push(RunOutcome {
+            name: format!("org {}", root.org),
+            failures,
+        });
+    }
+    if org_wide && let Some(teams) = crate::config::load_teams(config_base)? {
+        let mut failures = Vec::new();
+        let result = handle_teams(
+            mode,
+            &gh,
+            &teams,
+            options.prune_teams,
+            options.report,
+            &mut journal,
+        )
+        .await;
+        if let Some(section) = settle("updating teams", result, &mut failures, options.keep_going)?
+        {
+            org_report.sections.push(section);
+        }
+        for (step, err) in &failures {
+            eprintln!("Teams: failed while {step}: {err}");
+        }
+        outcomes.push(RunOutcome {
+            name: "teams".to_string(),
+            failures,
+        });
+    }
+
+    let result = handle_repos(mode, &gh, merged, &options, &mut journal, &mut outcomes).await;
+    if let Some(path) = journal.path() {
+        println!("Journal written to {}", path.display());
+    }
+    let reports = result?;
+    if options.keep_going {
+        println!("{}", format_run_summary(&outcomes));
+        let failed = outcomes.iter().filter(|o| !o.failures.is_empty()).count();
+        if failed > 0 {
+            return Err(Error::ReposFailed(failed));
+        }
+    }
+    let config_uri = root_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match options.report {
+        ReportFormat::Text => {}
+        ReportFormat::Markdown => {
+            println!("{}", render_markdown(&root.org, &org_report, &reports))
+        }
+        ReportFormat::Junit => println!("{}", render_junit(&root.org, &org_report, &reports)),
+        ReportFormat::Sarif => println!(
+            "{}",
+            render_sarif(&root.org, &config_uri, &org_report, &reports)
+        ),
+    }
+    Ok(())
 }
 
-async fn handle_repos(
+async fn handle_teams(
     mode: Mode,
     gh: &GithubClient,
-    merged: Vec<(String, MergedRepoConfig)>,
-    verbose: bool,
-) -> Result<()> {
-    for (repo_name, merged_cfg) in merged {
-        let repo_info = gh.get_repo(&repo_name).await?;
-        let base_branch = repo_info
-            .default_branch
-            .clone()
-            .unwrap_or_else(|| "main".to_string());
-
-        let (settings_diff, desired_settings) = if let Some(desired) = &merged_cfg.repo_settings {
-            let current = gh.get_repo_settings(&repo_name).await?;
-            (Some(diff_repo_settings(desired, &current)), Some(desired))
-        } else {
-            (None, None)
-        };
+    desired: &TeamsConfig,
+    prune: bool,
+    report: ReportFormat,
+    journal: &mut Journal,
+) -> Result<ReportSection> {
+    let current = gh.list_teams().await?;
+    let diff = diff_teams(desired, &current);
 
-        let existing_pr = gh
-            .find_open_pr_by_head_prefix(&repo_name, PR_BRANCH_PREFIX, &base_branch)
-            .await?;
-        let compare_branch = existing_pr.as_ref().map(|pr| pr.head.ref_field.clone());
-
-        let mut bp_changes: Vec<BranchProtectionChange> = Vec::new();
-        if let Some(cfg) = desired_settings.and_then(|s| s.branch_protection.as_ref()) {
-            for rule in &cfg.rules {
-                let current = gh.get_branch_protection(&repo_name, &rule.pattern).await?;
-                let target = merge_branch_rule(rule, current.as_ref());
-                if current.as_ref() != Some(&target) {
-                    bp_changes.push(BranchProtectionChange {
-                        pattern: rule.pattern.clone(),
-                        action: if current.is_some() {
-                            ChangeAction::Update
-                        } else {
-                            ChangeAction::Create
-                        },
-                        target,
-                    });
-                }
+    if matches!(mode, Mode::Apply) {
+        let mut slugs: HashMap<String, String> = HashMap::new();
+        for team in &current {
+            slugs.insert(team.slug.to_lowercase(), team.slug.clone());
+            slugs.insert(team.spec.name.to_lowercase(), team.slug.clone());
+        }
+        let parent_slug = |slugs: &HashMap<String, String>, spec: &TeamSpec| {
+            spec.parent
+                .as_ref()
+                .map(|p| slugs.get(&p.to_lowercase()).cloned().unwrap_or(p.clone()))
+        };
+        for spec in &diff.to_create {
+            journal.record_unrestorable(None, format!("created team {}", spec.name))?;
+            let parent = parent_slug(&slugs, spec);
+            let slug = gh.create_team(spec, parent.as_deref()).await?;
+            slugs.insert(spec.name.to_lowercase(), slug);
+        }
+        for update in &diff.to_update {
+            journal.record_unrestorable(
+                None,
+                format!(
+                    "updated team {} ({})",
+                    update.slug,
+                    update.fields.join(", ")
+                ),
+            )?;
+            let parent = parent_slug(&slugs, &update.spec);
+            let slug = gh
+                .update_team(&update.slug, &update.spec, parent.as_deref())
+                .await?;
+            slugs.insert(update.spec.name.to_lowercase(), slug);
+        }
+        for change in &diff.memberships {
+            let slug = slugs
+                .get(&change.team.to_lowercase())
+                .cloned()
+                .unwrap_or_else(|| change.team.clone());
+            journal.record_unrestorable(
+                None,
+                format!(
+                    "team {} membership of {}: {} -> {}",
+                    change.team,
+                    change.user,
+                    change.current.map(team_role_name).unwrap_or("none"),
+                    change.desired.map(team_role_name).unwrap_or("removed")
+                ),
+            )?;
+            match change.desired {
+                Some(role) => gh.set_team_membership(&slug, &change.user, role).await?,
+                None => gh.remove_team_membership(&slug, &change.user).await?,
+            }
+        }
+        if prune {
+            for slug in &diff.unmanaged {
+                journal.record_unrestorable(None, format!("deleted team {slug}"))?;
+                gh.delete_team(slug).await?;
             }
         }
+    }
 
-        let mut desired_templates: Vec<IssueTemplateFile> = merged_cfg
-            .issue_templates
-            .iter()
-            .filter(|t| !short_github_path(&t.path).ends_with("config.yml"))
-            .cloned()
-            .collect();
+    let section = teams_report(&diff, prune);
+    if report != ReportFormat::Text {
+        return Ok(section);
+    }
+    let stage = match mode {
+        Mode::Plan => "plan",
+        Mode::Apply => "apply",
+    };
+    let mut created = String::new();
+    for spec in &diff.to_create {
+        created.push_str(&format!(
+            "\n    - {}{}",
+            apply_color(&spec.name, ColorKind::Add),
+            spec.parent
+                .as_ref()
+                .map(|p| format!(" (parent: {p})"))
+                .unwrap_or_default()
+        ));
+    }
+    let mut updated = String::new();
+    for update in &diff.to_update {
+        let name = if update.fields.contains(&"name") {
+            format!("{} -> {}", update.slug, update.spec.name)
+        } else {
+            update.spec.name.clone()
+        };
+        updated.push_str(&format!(
+            "\n    - {} ({})",
+            apply_color(&name, ColorKind::Update),
+            update.fields.join(", ")
+        ));
+    }
+    let mut memberships = String::new();
+    for change in &diff.memberships {
+        let line = match (change.current, change.desired) {
+            (_, None) => apply_color(
+                &format!("{}: -{}", change.team, change.user),
+                ColorKind::Remove,
+            ),
+            (None, Some(role)) => apply_color(
+                &format!(
+                    "{}: +{} ({})",
+                    change.team,
+                    change.user,
+                    team_role_name(role)
+                ),
+                ColorKind::Add,
+            ),
+            (Some(from), Some(to)) => apply_color(
+                &format!(
+                    "{}: {} {} -> {}",
+                    change.team,
+                    change.user,
+                    team_role_name(from),
+                    team_role_name(to)
+                ),
+                ColorKind::Update,
+            ),
+        };
+        memberships.push_str(&format!("\n    - {line}"));
+    }
+    let (unmanaged_title, unmanaged_kind) = if prune {
+        ("Delete", ColorKind::Remove)
+    } else {
+        ("Unmanaged, kept without --prune-teams", ColorKind::Blocked)
+    };
+    let mut unmanaged = String::new();
+    for slug in &diff.unmanaged {
+        unmanaged.push_str(&format!("\n    - {}", apply_color(slug, unmanaged_kind)));
+    }
+    let or_none = |s: String| if s.is_empty() { " none".to_string() } else { s };
+    println!(
+        "Teams ({}):\n  Create ({}) :{}\n  Update ({}) :{}\n  Memberships ({}) :{}\n  {} ({}) :{}",
+        stage,
+        format_count(diff.to_create.len(), ColorKind::Add),
+        or_none(created),
+        format_count(diff.to_update.len(), ColorKind::Update),
+        or_none(updated),
+        format_count(diff.memberships.len(), ColorKind::Update),
+        or_none(memberships),
+        unmanaged_title,
+        format_count(diff.unmanaged.len(), unmanaged_kind),
+        or_none(unmanaged),
+    );
+    Ok(section)
+}
 
-        if let Some(cfg) = build_issue_template_config(&merged_cfg.issue_templates) {
-            desired_templates.push(cfg);
+/// Report section for teams; unmanaged teams only count as changes when they get deleted.
+fn teams_report(diff: &TeamsDiff, prune: bool) -> ReportSection {
+    let mut details: Vec<String> = diff
+        .to_create
+        .iter()
+        .map(|spec| format!("- create team `{}`", spec.name))
+        .collect();
+    details.extend(
+        diff.to_update
+            .iter()
+            .map(|u| format!("- update team `{}` ({})", u.slug, u.fields.join(", "))),
+    );
+    details.extend(
+        diff.memberships
+            .iter()
+            .map(|c| match (c.current, c.desired) {
+                (_, None) => format!("- remove `{}` from `{}`", c.user, c.team),
+                (None, Some(role)) => format!(
+                    "- add `{}` to `{}` as {}",
+                    c.user,
+                    c.team,
+                    team_role_name(role)
+                ),
+                (Some(from), Some(to)) => format!(
+                    "- change `{}` in `{}` from {} to {}",
+                    c.user,
+                    c.team,
+                    team_role_name(from),
+                    team_role_name(to)
+                ),
+            }),
+    );
+    let mut count = details.len();
+    if prune {
+        count += diff.unmanaged.len();
+    }
+    details.extend(diff.unmanaged.iter().map(|slug| {
+        if prune {
+            format!("- delete team `{slug}`")
+        } else {
+            format!("- unmanaged team `{slug}`, kept without --prune-teams")
         }
+    }));
+    ReportSection {
+        name: "Teams",
+        count: Some(count),
+        details,
+    }
+}
 
-        let mut templates_add: Vec<IssueTemplateFile> = Vec::new();
-        let mut templates_update: Vec<(IssueTemplateFile, String)> = Vec::new();
-        let mut templates_remove: Vec<(String, String)> = Vec::new(); // (path, sha)
-        for tpl in &desired_templates {
-            match gh
-                .get_file(&repo_name, &tpl.path, compare_branch.as_deref())
-                .await?
-            {
-                None => templates_add.push(tpl.clone()),
-                Some(file) if file.content != tpl.contents => {
-                    templates_update.push((tpl.clone(), file.sha))
-                }
-                _ => {}
-            }
+fn team_role_name(role: TeamRole) -> &'static str {
+    match role {
+        TeamRole::Member => "member",
+        TeamRole::Maintainer => "maintainer",
+    }
+}
+
+fn access_kind_name(kind: AccessKind) -> &'static str {
+    match kind {
+        AccessKind::Team => "team",
+        AccessKind::Collaborator => "collaborator",
+    }
+}
+
+async fn handle_org(
+    mode: Mode,
+    gh: &GithubClient,
+    org: &str,
+    desired: &OrgSettings,
+    report: ReportFormat,
+    journal: &mut Journal,
+) -> Result<ReportSection> {
+    let current = gh.get_org_settings().await?;
+    let diff = diff_org_settings(desired, &current);
+    if matches!(mode, Mode::Apply) && !diff.changes.is_empty() {
+        for change in &diff.changes {
+            journal.record_unrestorable(
+                None,
+                format!(
+                    "org setting {}: {} -> {}",
+                    change.field,
+                    change.current.as_deref().unwrap_or("unset"),
+                    change.desired
+                ),
+            )?;
         }
-        if let Some(branch_ref) = compare_branch.as_deref() {
-            let current_paths = gh
-                .list_github_files(&repo_name, branch_ref, ".github/")
-                .await
-                .unwrap_or_default();
-            for path in current_paths {
-                if path.starts_with(".github/ISSUE_TEMPLATE/")
-                    && !desired_templates
-                        .iter()
-                        .any(|t| short_github_path(&t.path) == path)
-                {
-                    if let Some(file) = gh
-                        .get_file(&repo_name, &path, compare_branch.as_deref())
-                        .await?
-                    {
-                        templates_remove.push((path, file.sha));
+        gh.update_org_settings(desired).await?;
+    }
+
+    // The two-factor requirement and default labels are manual, so they are listed but not
+    // counted.
+    let mut details = setting_change_details(&diff.changes);
+    if let (Some(change), Some(_)) = (&diff.two_factor, desired.two_factor_requirement_enabled) {
+        details.push(format!(
+            "- `{}`: {} -> {} (manual, in the organization security settings)",
+            change.field,
+            change.current.as_deref().unwrap_or("unknown"),
+            change.desired
+        ));
+    }
+    details.extend(desired.default_labels.iter().map(|l| {
+        format!(
+            "- default label `{}` (manual, under Repository defaults)",
+            l.name
+        )
+    }));
+    let section = ReportSection {
+        name: "Org settings",
+        count: Some(diff.changes.len()),
+        details,
+    };
+    if report != ReportFormat::Text {
+        return Ok(section);
+    }
+
+    let two_factor = match (&diff.two_factor, desired.two_factor_requirement_enabled) {
+        (_, None) => " not configured".to_string(),
+        (None, Some(_)) => " ok".to_string(),
+        (Some(change), Some(_)) => format!(
+            " {} -> {} ({})",
+            change
+                .current
+                .as_deref()
+                .unwrap_or("unknown (owner access required)"),
+            change.desired,
+            apply_color(
+                "change in the organization security settings",
+                ColorKind::Blocked
+            )
+        ),
+    };
+    let labels = if desired.default_labels.is_empty() {
+        " not configured".to_string()
+    } else {
+        format!(
+            " {}{}",
+            apply_color(
+                "not available via the API; set under organization settings > Repository defaults",
+                ColorKind::Blocked
+            ),
+            format_label_lines(&desired.default_labels, ColorKind::Blocked)
+        )
+    };
+    let stage = match mode {
+        Mode::Plan => "plan",
+        Mode::Apply => "apply",
+    };
+    let changes = if diff.changes.is_empty() {
+        " none".to_string()
+    } else {
+        format_setting_changes(&diff.changes)
+    };
+    println!(
+        "Org {} ({}):\n  Settings changes ({}) :{}\n  Two-factor requirement :{}\n  Default repository labels (manual) :{}",
+        org,
+        stage,
+        format_count(diff.changes.len(), ColorKind::Update),
+        changes,
+        two_factor,
+        labels,
+    );
+    Ok(section)
+}
+
+async fn handle_repos(
+    mode: Mode,
+    gh: &GithubClient,
+    merged: Vec<(String, Result<MergedRepoConfig>)>,
+    options: &RunOptions,
+    journal: &mut Journal,
+    outcomes: &mut Vec<RunOutcome>,
+) -> Result<Vec<RepoReport>> {
+    let mut reports: Vec<RepoReport> = Vec::new();
+    for (repo_name, merged_cfg) in merged {
+        let mut failures = Vec::new();
+        match merged_cfg {
+            Err(err) => failures.push(("loading configuration", err.to_string())),
+            Ok(merged_cfg) => {
+                let outcome = handle_repo(
+                    mode,
+                    gh,
+                    repo_name.clone(),
+                    merged_cfg,
+                    options,
+                    journal,
+                    &mut failures,
+                )
+                .await;
+                match outcome {
+                    Ok(report) => reports.extend(report),
+                    Err(err) if options.keep_going => {
+                        failures.push(("reading current state", err.to_string()))
                     }
+                    Err(err) => return Err(err),
                 }
             }
         }
-        let any_file_changes = !templates_add.is_empty()
-            || !templates_update.is_empty()
-            || !templates_remove.is_empty();
+        for (step, err) in &failures {
+            eprintln!("Repo {repo_name}: failed while {step}: {err}");
+        }
+        outcomes.push(RunOutcome {
+            name: repo_name,
+            failures,
+        });
+    }
+    Ok(reports)
+}
+
+/// Under `--keep-going` a failed step is recorded and the caller carries on; otherwise the
+/// error is returned.
+fn settle<T>(
+    step: &'static str,
+    result: Result<T>,
+    failures: &mut Vec<(&'static str, String)>,
+    keep_going: bool,
+) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if keep_going => {
+            failures.push((step, err.to_string()));
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Result of one repository, or of org settings or teams, under `--keep-going`.
+struct RunOutcome {
+    name: String,
+    /// The operations that failed and their errors.
+    failures: Vec<(&'static str, String)>,
+}
 
-        let current_labels = gh.list_repo_labels(&repo_name).await?;
-        let diff = diff_labels(&merged_cfg.labels, &current_labels);
+fn format_run_summary(outcomes: &[RunOutcome]) -> String {
+    let failed = outcomes.iter().filter(|o| !o.failures.is_empty()).count();
+    let mut out = format!(
+        "Summary: {} succeeded, {} failed",
+        format_count(outcomes.len() - failed, ColorKind::Add),
+        format_count(failed, ColorKind::Blocked)
+    );
+    for outcome in outcomes {
+        if outcome.failures.is_empty() {
+            out.push_str(&format!("\n  ok      {}", outcome.name));
+        }
+        for (step, err) in &outcome.failures {
+            out.push_str(&format!(
+                "\n  {}  {} ({step}): {err}",
+                apply_color("FAILED", ColorKind::Blocked),
+                outcome.name
+            ));
+        }
+    }
+    out
+}
 
-        let mut blocked_removals: Vec<(LabelSpec, Vec<LabelUsageEntry>)> = Vec::new();
-        let mut removable = Vec::new();
+/// Plans or applies one repository. Under `--keep-going`, apply records failed steps in
+/// `failures` and carries on with the steps that do not depend on them.
+async fn handle_repo(
+    mode: Mode,
+    gh: &GithubClient,
+    repo_name: String,
+    merged_cfg: MergedRepoConfig,
+    options: &RunOptions,
+    journal: &mut Journal,
+    failures: &mut Vec<(&'static str, String)>,
+) -> Result<Option<RepoReport>> {
+    let verbose = options.verbose;
+    let repo_info = gh.get_repo(&repo_name).await?;
+    let base_branch = repo_info
+        .default_branch
+        .clone()
+        .unwrap_or_else(|| "main".to_string());
 
-        for label in &diff.to_remove {
-            match gh.label_usage(&repo_name, &label.name, verbose).await? {
-                Some(usage) => blocked_removals.push((label.clone(), usage)),
-                None => removable.push(label.clone()),
+    let (settings_diff, desired_settings, current_settings) =
+        if let Some(desired) = &merged_cfg.repo_settings {
+            let mut current = gh.get_repo_settings(&repo_name).await?;
+            if desired.actions.is_some() {
+                current.actions = gh.get_actions_settings(&repo_name).await?;
+            }
+            if desired.security.is_some() {
+                current.security = Some(gh.get_security_settings(&repo_name).await?);
             }
+            (
+                Some(diff_repo_settings(desired, &current)),
+                Some(desired),
+                Some(current),
+            )
+        } else {
+            (None, None, None)
+        };
+
+    let existing_pr = gh
+        .find_open_pr_by_head_prefix(&repo_name, PR_BRANCH_PREFIX, &base_branch)
+        .await?;
+    // Direct delivery compares against the default branch it commits to.
+    let direct = merged_cfg.file_delivery == Some(FileDelivery::Direct);
+    let stale = match &existing_pr {
+        Some(pr) if !direct => {
+            gh.pr_staleness(&repo_name, pr.number, &base_branch, &pr.head.ref_field)
+                .await?
         }
+        _ => None,
+    };
+    // A stale branch is only rebuilt while every commit on it came from gh-governor; commits
+    // pushed by people are never discarded.
+    let foreign_commits = match (&existing_pr, stale) {
+        (Some(pr), Some(_)) => gh
+            .branch_commit_messages(&repo_name, &base_branch, &pr.head.ref_field)
+            .await?
+            .iter()
+            .filter(|m| !m.starts_with(COMMIT_SUBJECT))
+            .count(),
+        _ => 0,
+    };
+    let staleness = stale.filter(|_| foreign_commits == 0);
+    // A stale branch is rebuilt from the base, so files are compared against the base.
+    let compare_branch = if direct {
+        None
+    } else if staleness.is_some() {
+        Some(base_branch.clone())
+    } else {
+        existing_pr.as_ref().map(|pr| pr.head.ref_field.clone())
+    };
 
-        match mode {
-            Mode::Plan => {
-                let (settings_count, settings_lines) = format_repo_settings(settings_diff.as_ref());
-                let (bp_count, bp_lines) = format_branch_protection(&bp_changes, verbose);
-                let (pr_note, pr_branch_display) = if any_file_changes {
-                    if let Some(pr) = &existing_pr {
-                        let branch = pr.head.ref_field.clone();
-                        (
-                            format!(
-                                "draft PR will be updated for .github file updates (reusing #{})",
-                                pr.number
-                            ),
-                            Some(branch),
-                        )
+    let mut bp_changes: Vec<BranchProtectionChange> = Vec::new();
+    let protection_configured = desired_settings
+        .and_then(|s| s.branch_protection.as_ref())
+        .is_some();
+    if let Some(cfg) = desired_settings.and_then(|s| s.branch_protection.as_ref()) {
+        for rule in &cfg.rules {
+            let current = gh.get_branch_protection(&repo_name, &rule.pattern).await?;
+            let target = merge_branch_rule(rule, current.as_ref());
+            if current.as_ref() != Some(&target) {
+                bp_changes.push(BranchProtectionChange {
+                    pattern: rule.pattern.clone(),
+                    action: if current.is_some() {
+                        ChangeAction::Update
                     } else {
-                        let branch_name = format!("{PR_BRANCH_PREFIX}{base_branch}");
-                        (
-                            "draft PR will be created for .github file updates".to_string(),
-                            Some(branch_name),
-                        )
-                    }
-                } else if let Some(pr) = &existing_pr {
+                        ChangeAction::Create
+                    },
+                    target,
+                    current,
+                });
+            }
+        }
+    }
+
+    let access_diff = match &merged_cfg.access {
+        Some(desired) => {
+            let current = gh.get_access(&repo_name).await?;
+            Some(diff_access(desired, &current))
+        }
+        None => None,
+    };
+
+    let webhook_diff = match &merged_cfg.webhooks {
+        Some(desired) => {
+            let current = gh.list_webhooks(&repo_name).await?;
+            Some(diff_webhooks(desired, &current))
+        }
+        None => None,
+    };
+
+    let environment_changes = match &merged_cfg.environments {
+        Some(desired) => {
+            let current = gh.list_environments(&repo_name).await?;
+            Some(diff_environments(desired, &current))
+        }
+        None => None,
+    };
+
+    let autolink_diff = match &merged_cfg.autolinks {
+        Some(desired) => {
+            let current = gh.list_autolinks(&repo_name).await?;
+            Some(diff_autolinks(desired, &current))
+        }
+        None => None,
+    };
+
+    let property_changes = match &merged_cfg.properties {
+        Some(desired) => {
+            let current = gh.get_property_values(&repo_name).await?;
+            Some(diff_properties(desired, &current))
+        }
+        None => None,
+    };
+
+    let milestone_diff = match &merged_cfg.milestones {
+        Some(desired) => {
+            let current = gh.list_milestones(&repo_name).await?;
+            Some(diff_milestones(desired, &current))
+        }
+        None => None,
+    };
+
+    let discussions_diff = match &merged_cfg.discussions {
+        Some(desired) => {
+            let current = gh.get_discussions(&repo_name).await?;
+            Some(diff_discussions(desired, &current))
+        }
+        None => None,
+    };
+
+    let mut desired_templates: Vec<IssueTemplateFile> = merged_cfg
+        .issue_templates
+        .iter()
+        .filter(|t| !short_github_path(&t.path).ends_with("config.yml"))
+        .cloned()
+        .collect();
+
+    if let Some(cfg) = build_issue_template_config(&merged_cfg.issue_templates) {
+        desired_templates.push(cfg);
+    }
+
+    let FileChanges {
+        add: templates_add,
+        update: templates_update,
+        remove: templates_remove,
+        previous: previous_contents,
+    } = compute_file_changes(
+        gh,
+        &repo_name,
+        &desired_templates,
+        compare_branch.as_deref(),
+    )
+    .await?;
+    let any_file_changes =
+        !templates_add.is_empty() || !templates_update.is_empty() || !templates_remove.is_empty();
+
+    // An open PR is obsolete once the default branch already holds the desired files.
+    let obsolete_pr = match &existing_pr {
+        Some(_) if direct || staleness.is_some() => !any_file_changes,
+        Some(_) => compute_file_changes(gh, &repo_name, &desired_templates, Some(&base_branch))
+            .await?
+            .is_empty(),
+        None => false,
+    };
+
+    let current_labels = gh.list_repo_labels(&repo_name).await?;
+    let diff = diff_labels(&merged_cfg.labels, &current_labels);
+
+    let mut blocked_removals: Vec<(LabelSpec, Vec<LabelUsageEntry>)> = Vec::new();
+    let mut removable = Vec::new();
+
+    for label in &diff.to_remove {
+        match gh.label_usage(&repo_name, &label.name, verbose).await? {
+            Some(usage) => blocked_removals.push((label.clone(), usage)),
+            None => removable.push(label.clone()),
+        }
+    }
+
+    match mode {
+        Mode::Plan => {
+            let (settings_count, settings_lines) = format_repo_settings(settings_diff.as_ref());
+            let (bp_count, bp_lines) = format_branch_protection(
+                protection_configured.then_some(bp_changes.as_slice()),
+                verbose,
+            );
+            let (access_count, access_lines) = format_access(access_diff.as_ref());
+            let (webhook_count, webhook_lines) = format_webhooks(webhook_diff.as_ref());
+            let (env_count, env_lines) = format_environments(environment_changes.as_deref());
+            let (autolink_count, autolink_lines) = format_autolinks(autolink_diff.as_ref());
+            let (property_count, property_lines) = format_properties(property_changes.as_deref());
+            let (milestone_count, milestone_lines) = format_milestones(milestone_diff.as_ref());
+            let (discussion_count, discussion_lines) =
+                format_discussions(discussions_diff.as_ref());
+            let (pr_note, pr_branch_display) = if any_file_changes {
+                if let Some(pr) = &existing_pr {
+                    let branch = pr.head.ref_field.clone();
                     (
                         format!(
-                            "existing draft PR #{} already present for .github files",
+                            "{} will be updated for .github file updates (reusing #{})",
+                            pr_kind(pr.draft.unwrap_or(false)),
                             pr.number
                         ),
-                        Some(pr.head.ref_field.clone()),
+                        Some(branch),
+                    )
+                } else {
+                    let branch_name = format!("{PR_BRANCH_PREFIX}{base_branch}");
+                    (
+                        format!(
+                            "{} will be created for .github file updates",
+                            pr_kind(merged_cfg.pull_request.is_draft())
+                        ),
+                        Some(branch_name),
+                    )
+                }
+            } else if let Some(pr) = &existing_pr {
+                (
+                    format!(
+                        "existing {} #{} already present for .github files",
+                        pr_kind(pr.draft.unwrap_or(false)),
+                        pr.number
+                    ),
+                    Some(pr.head.ref_field.clone()),
+                )
+            } else {
+                ("no PR (no .github file changes)".to_string(), None)
+            };
+            let (pr_note, pr_branch_display) = match (&existing_pr, staleness) {
+                (Some(pr), _) if obsolete_pr => (
+                    format!(
+                        "PR #{} is obsolete (files already match '{base_branch}'); it will be closed and its branch deleted",
+                        pr.number
+                    ),
+                    None,
+                ),
+                (Some(pr), Some(reason)) => {
+                    let action = match merged_cfg.pull_request.stale_branch.unwrap_or_default() {
+                        StaleBranchAction::Recreate => {
+                            format!("branch will be recreated from '{base_branch}'")
+                        }
+                        StaleBranchAction::Reopen => {
+                            "it will be closed and replaced by a new PR".to_string()
+                        }
+                    };
+                    (
+                        format!("PR #{} is stale ({reason}); {action}", pr.number),
+                        pr_branch_display,
+                    )
+                }
+                _ => (pr_note, pr_branch_display),
+            };
+            let pr_note = match stale {
+                Some(reason) if foreign_commits > 0 => format!(
+                    "{pr_note}; PR is stale ({reason}) but its branch has {foreign_commits} commit(s) not made by gh-governor, so it is left as is"
+                ),
+                _ => pr_note,
+            };
+            let (pr_note, pr_branch_display) = if direct && any_file_changes {
+                let protection = gh.get_branch_protection(&repo_name, &base_branch).await?;
+                if protection.as_ref().is_some_and(blocks_direct_push) {
+                    (
+                        format!(
+                            "direct commit to '{base_branch}' blocked by branch protection; {pr_note}"
+                        ),
+                        pr_branch_display,
                     )
                 } else {
-                    ("no PR (no .github file changes)".to_string(), None)
+                    (format!("will commit directly to '{base_branch}'"), None)
+                }
+            } else {
+                (pr_note, pr_branch_display)
+            };
+            if options.report != ReportFormat::Text {
+                let files = [
+                    templates_add
+                        .iter()
+                        .map(|t| format!("- add `{}`", short_github_path(&t.path)))
+                        .collect::<Vec<_>>(),
+                    templates_update
+                        .iter()
+                        .map(|(t, _)| format!("- update `{}`", short_github_path(&t.path)))
+                        .collect(),
+                    templates_remove
+                        .iter()
+                        .map(|(p, _)| format!("- remove `{p}`"))
+                        .collect(),
+                ]
+                .concat();
+                let labels = [
+                    diff.to_add
+                        .iter()
+                        .map(|l| format!("- add `{}`", l.name))
+                        .collect::<Vec<_>>(),
+                    diff.to_update
+                        .iter()
+                        .map(|l| format!("- update `{}`", l.name))
+                        .collect(),
+                    removable
+                        .iter()
+                        .map(|l| format!("- remove `{}`", l.name))
+                        .collect(),
+                ]
+                .concat();
+                let codeowners = match &merged_cfg.checks {
+                    Some(checks) if checks.require_codeowners => {
+                        let mut found = false;
+                        for path in CODEOWNERS_PATHS {
+                            if gh.get_file(&repo_name, path, None).await?.is_some() {
+                                found = true;
+                                break;
+                            }
+                        }
+                        Some(found)
+                    }
+                    _ => None,
                 };
+                return Ok(Some(RepoReport {
+                    repo: repo_name.clone(),
+                    sections: vec![
+                        report_section("Settings", settings_diff.as_ref().map(settings_report)),
+                        report_section(
+                            "Protection",
+                            protection_configured.then_some(protection_report(&bp_changes)),
+                        ),
+                        report_section("Access", access_diff.as_ref().map(access_report)),
+                        report_section("Webhooks", webhook_diff.as_ref().map(webhooks_report)),
+                        report_section(
+                            "Environments",
+                            environment_changes.as_deref().map(environments_report),
+                        ),
+                        report_section("Autolinks", autolink_diff.as_ref().map(autolinks_report)),
+                        report_section(
+                            "Properties",
+                            property_changes.as_deref().map(properties_report),
+                        ),
+                        report_section(
+                            "Milestones",
+                            milestone_diff.as_ref().map(milestones_report),
+                        ),
+                        report_section(
+                            "Discussions",
+                            discussions_diff.as_ref().map(discussions_report),
+                        ),
+                        ReportSection {
+                            name: "Files",
+                            count: Some(files.len()),
+                            details: files,
+                        },
+                        ReportSection {
+                            name: "Labels",
+                            count: Some(labels.len()),
+                            details: labels,
+                        },
+                    ],
+                    blocked: blocked_removals
+                        .iter()
+                        .map(|(label, usage)| BlockedLabel {
+                            label: label.name.clone(),
+                            usage: usage.clone(),
+                        })
+                        .collect(),
+                    pr_note: match &pr_branch_display {
+                        Some(branch) => format!("{pr_note} on branch `{branch}`"),
+                        None => pr_note,
+                    },
+                    codeowners,
+                }));
+            }
+            println!(
+                "Repo {} (plan):\n  Repo settings changes ({}) :{}\n  Branch protection ({}) :{}\n  Access ({}) :{}\n  Webhooks ({}) :{}\n  Environments ({}) :{}\n  Autolinks ({}) :{}\n  Custom properties ({}) :{}\n  Milestones ({}) :{}\n  Discussions ({}) :{}\n  PR:\n    {}{}\n    .github files add ({}) :{}\n    .github files update ({}) :{}\n    .github files remove ({}) :{}\n  Add labels ({}) :{}\n  Update labels ({}) :{}\n  Remove labels ({}) :{}\n  Blocked removals ({}) :{}",
+                repo_name,
+                settings_count,
+                settings_lines,
+                bp_count,
+                bp_lines,
+                access_count,
+                access_lines,
+                webhook_count,
+                webhook_lines,
+                env_count,
+                env_lines,
+                autolink_count,
+                autolink_lines,
+                property_count,
+                property_lines,
+                milestone_count,
+                milestone_lines,
+                discussion_count,
+                discussion_lines,
+                pr_note,
+                pr_branch_display
+                    .as_ref()
+                    .map(|b| format!(" on branch '{}'\n", b))
+                    .unwrap_or_else(String::new),
+                format_count(templates_add.len(), ColorKind::Add),
+                format_template_lines(&templates_add, ColorKind::Add),
+                format_count(templates_update.len(), ColorKind::Update),
+                format_template_lines(
+                    &templates_update
+                        .iter()
+                        .map(|(t, _)| t.clone())
+                        .collect::<Vec<_>>(),
+                    ColorKind::Update
+                ),
+                format_count(templates_remove.len(), ColorKind::Remove),
+                format_remove_lines(&templates_remove),
+                format_count(diff.to_add.len(), ColorKind::Add),
+                format_label_lines(&diff.to_add, ColorKind::Add),
+                format_count(diff.to_update.len(), ColorKind::Update),
+                format_label_lines(&diff.to_update, ColorKind::Update),
+                format_count(removable.len(), ColorKind::Remove),
+                format_label_lines(&removable, ColorKind::Remove),
+                format_count(blocked_removals.len(), ColorKind::Blocked),
+                format_blocked_lines(&blocked_removals, verbose),
+            );
+            if options.show_diff && (!templates_add.is_empty() || !templates_update.is_empty()) {
                 println!(
-                    "Repo {} (plan):\n  Repo settings changes ({}) :{}\n  Branch protection ({}) :{}\n  PR:\n    {}{}\n    .github files add ({}) :{}\n    .github files update ({}) :{}\n    .github files remove ({}) :{}\n  Add labels ({}) :{}\n  Update labels ({}) :{}\n  Remove labels ({}) :{}\n  Blocked removals ({}) :{}",
-                    repo_name,
-                    settings_count,
-                    settings_lines,
-                    bp_count,
-                    bp_lines,
-                    pr_note,
-                    pr_branch_display
-                        .as_ref()
-                        .map(|b| format!(" on branch '{}'\n", b))
-                        .unwrap_or_else(String::new),
-                    format_count(templates_add.len(), ColorKind::Add),
-                    format_template_lines(&templates_add, ColorKind::Add),
-                    format_count(templates_update.len(), ColorKind::Update),
-                    format_template_lines(
-                        &templates_update
-                            .iter()
-                            .map(|(t, _)| t.clone())
-                            .collect::<Vec<_>>(),
-                        ColorKind::Update
-                    ),
-                    format_count(templates_remove.len(), ColorKind::Remove),
-                    format_remove_lines(&templates_remove),
-                    format_count(diff.to_add.len(), ColorKind::Add),
-                    format_label_lines(&diff.to_add, ColorKind::Add),
-                    format_count(diff.to_update.len(), ColorKind::Update),
-                    format_label_lines(&diff.to_update, ColorKind::Update),
-                    format_count(removable.len(), ColorKind::Remove),
-                    format_label_lines(&removable, ColorKind::Remove),
-                    format_count(blocked_removals.len(), ColorKind::Blocked),
-                    format_blocked_lines(&blocked_removals, verbose),
+                    "  .github file diffs:{}",
+                    format_file_diffs(
+                        &templates_add,
+                        &templates_update,
+                        &previous_contents,
+                        options.diff_max_lines
+                    )
                 );
             }
-            Mode::Apply => {
+        }
+        Mode::Apply => {
+            let result: Result<()> = async {
                 if let (Some(diff_settings), Some(desired)) = (&settings_diff, desired_settings) {
-                    if !diff_settings.changes.is_empty() {
+                    if let Some(before) = current_settings.as_ref()
+                        && !diff_settings.changes.is_empty()
+                    {
+                        journal.record(&JournalEntry::RepoSettings {
+                            repo: repo_name.clone(),
+                            before: before.clone(),
+                        })?;
+                    }
+                    let changed =
+                        |category| diff_settings.changes.iter().any(|c| c.category == category);
+                    if changed(SettingCategory::PullRequests) {
                         gh.update_repo_settings(&repo_name, desired).await?;
                     }
+                    if let Some(actions) = &desired.actions
+                        && changed(SettingCategory::Actions)
+                    {
+                        gh.update_actions_settings(&repo_name, actions).await?;
+                    }
+                    if let Some(security) = &desired.security {
+                        let pending = pending_security_changes(security, diff_settings);
+                        if pending != SecuritySettings::default() {
+                            gh.update_security_settings(&repo_name, &pending).await?;
+                        }
+                    }
                 }
+                Ok(())
+            }
+            .await;
+            settle(
+                "updating repo settings",
+                result,
+                failures,
+                options.keep_going,
+            )?;
 
+            let result: Result<()> = async {
                 for bp in &bp_changes {
+                    journal.record(&JournalEntry::BranchProtection {
+                        repo: repo_name.clone(),
+                        pattern: bp.pattern.clone(),
+                        before: bp.current.clone(),
+                    })?;
                     gh.set_branch_protection(&repo_name, &bp.target).await?;
                 }
+                Ok(())
+            }
+            .await;
+            settle(
+                "updating branch protection",
+                result,
+                failures,
+                options.keep_going,
+            )?;
 
-                let any_file_changes = !templates_add.is_empty() || !templates_update.is_empty();
-                let existing_pr = if any_file_changes || existing_pr.is_some() {
-                    gh.find_open_pr_by_head_prefix(&repo_name, PR_BRANCH_PREFIX, &base_branch)
-                        .await?
-                } else {
-                    None
-                };
-                let branch_name = if let Some(pr) = &existing_pr {
-                    Some(pr.head.ref_field.clone())
-                } else if any_file_changes {
-                    let name = format!("{PR_BRANCH_PREFIX}{}", base_branch);
-                    let base_sha = gh.get_branch_sha(&repo_name, &base_branch).await?;
-                    gh.create_branch_from(&repo_name, &name, &base_sha).await?;
-                    Some(name)
-                } else {
-                    None
-                };
+            let result: Result<()> = async {
+                if let Some(access) = &access_diff {
+                    for change in access
+                        .to_add
+                        .iter()
+                        .chain(&access.to_update)
+                        .chain(&access.to_remove)
+                    {
+                        journal.record_unrestorable(
+                            Some(&repo_name),
+                            format!(
+                                "{} '{}' access {} -> {}",
+                                access_kind_name(change.kind),
+                                change.name,
+                                change.current.map(|p| p.as_str()).unwrap_or("none"),
+                                change.desired.map(|p| p.as_str()).unwrap_or("removed")
+                            ),
+                        )?;
+                    }
+                    for change in access.to_add.iter().chain(&access.to_update) {
+                        let Some(perm) = change.desired else { continue };
+                        match change.kind {
+                            AccessKind::Team => {
+                                gh.set_team_permission(&repo_name, &change.name, perm)
+                                    .await?
+                            }
+                            AccessKind::Collaborator => {
+                                gh.set_collaborator_permission(&repo_name, &change.name, perm)
+                                    .await?
+                            }
+                        }
+                    }
+                    for change in &access.to_remove {
+                        match change.kind {
+                            AccessKind::Team => {
+                                gh.remove_team_access(&repo_name, &change.name).await?
+                            }
+                            AccessKind::Collaborator => {
+                                gh.remove_collaborator(&repo_name, &change.name).await?
+                            }
+                        }
+                    }
+                }
+                Ok(())
+            }
+            .await;
+            settle("updating access", result, failures, options.keep_going)?;
 
-                if let Some(branch_ref) = branch_name.as_deref() {
-                    for tpl in &templates_add {
-                        let msg = format!("Add .github file {} via gh-governor", tpl.path);
-                        gh.put_file(
-                            &repo_name,
-                            &tpl.path,
-                            &tpl.contents,
-                            None,
-                            &msg,
-                            Some(branch_ref),
-                        )
-                        .await?;
+            let result: Result<()> = async {
+                if let Some(hooks) = &webhook_diff {
+                    for hook in &hooks.to_add {
+                        let secret = resolve_webhook_secret(hook)?;
+                        journal.record_unrestorable(
+                            Some(&repo_name),
+                            format!("created webhook {}", hook.url),
+                        )?;
+                        gh.create_webhook(&repo_name, hook, secret.as_deref())
+                            .await?;
                     }
-                    for (tpl, sha) in &templates_update {
-                        let msg = format!("Update .github file {} via gh-governor", tpl.path);
-                        gh.put_file(
+                    for update in &hooks.to_update {
+                        if update.loses_secret() {
+                            return Err(Error::WebhookSecretRequired {
+                                repo: repo_name.clone(),
+                                url: update.spec.url.clone(),
+                            });
+                        }
+                        let secret = if update.config_changed {
+                            resolve_webhook_secret(&update.spec)?
+                        } else {
+                            None
+                        };
+                        journal.record_unrestorable(
+                            Some(&repo_name),
+                            format!("updated webhook {}", update.spec.url),
+                        )?;
+                        gh.update_webhook(
                             &repo_name,
-                            &tpl.path,
-                            &tpl.contents,
-                            Some(sha.clone()),
-                            &msg,
-                            Some(branch_ref),
+                            update.id,
+                            &update.spec,
+                            update.config_changed,
+                            secret.as_deref(),
                         )
                         .await?;
                     }
-                    for (path, sha) in &templates_remove {
-                        let msg = format!("Remove .github file {} via gh-governor", path);
-                        gh.delete_file(&repo_name, path, sha, &msg, Some(branch_ref))
-                            .await?;
+                    for (id, url) in &hooks.to_remove {
+                        journal.record_unrestorable(
+                            Some(&repo_name),
+                            format!("deleted webhook {url}"),
+                        )?;
+                        gh.delete_webhook(&repo_name, *id).await?;
+                    }
+                }
+                Ok(())
+            }
+            .await;
+            settle("updating webhooks", result, failures, options.keep_going)?;
+
+            let result: Result<()> = async {
+                for change in environment_changes.iter().flatten() {
+                    journal.record_unrestorable(
+                        Some(&repo_name),
+                        format!(
+                            "{} environment {}",
+                            if change.exists { "updated" } else { "created" },
+                            change.spec.name
+                        ),
+                    )?;
+                    gh.upsert_environment(&repo_name, &change.spec).await?;
+                }
+                Ok(())
+            }
+            .await;
+            settle(
+                "updating environments",
+                result,
+                failures,
+                options.keep_going,
+            )?;
+
+            let result: Result<()> = async {
+                if let Some(links) = &autolink_diff {
+                    for (id, link) in links.to_replace.iter().chain(&links.to_remove) {
+                        journal.record_unrestorable(
+                            Some(&repo_name),
+                            format!("deleted autolink {}", link.key_prefix),
+                        )?;
+                        gh.delete_autolink(&repo_name, *id).await?;
+                    }
+                    for link in links
+                        .to_add
+                        .iter()
+                        .chain(links.to_replace.iter().map(|(_, l)| l))
+                    {
+                        journal.record_unrestorable(
+                            Some(&repo_name),
+                            format!("created autolink {}", link.key_prefix),
+                        )?;
+                        gh.create_autolink(&repo_name, link).await?;
+                    }
+                }
+                Ok(())
+            }
+            .await;
+            settle("updating autolinks", result, failures, options.keep_going)?;
+
+            let result: Result<()> = async {
+                if let Some(changes) = property_changes.as_ref().filter(|c| !c.is_empty()) {
+                    for change in changes {
+                        journal.record_unrestorable(
+                            Some(&repo_name),
+                            format!(
+                                "custom property {}: {} -> {}",
+                                change.name,
+                                change
+                                    .current
+                                    .as_ref()
+                                    .map(|v| v.to_string())
+                                    .unwrap_or_else(|| "unset".to_string()),
+                                change.desired
+                            ),
+                        )?;
+                    }
+                    let values: Vec<_> = changes
+                        .iter()
+                        .map(|c| (c.name.as_str(), &c.desired))
+                        .collect();
+                    gh.update_property_values(&repo_name, &values).await?;
+                }
+                Ok(())
+            }
+            .await;
+            settle(
+                "updating custom properties",
+                result,
+                failures,
+                options.keep_going,
+            )?;
+
+            let result: Result<()> = async {
+                if let Some(enabled) = discussions_diff.as_ref().and_then(|d| d.enable) {
+                    journal.record_unrestorable(
+                        Some(&repo_name),
+                        format!(
+                            "{} discussions",
+                            if enabled { "enabled" } else { "disabled" }
+                        ),
+                    )?;
+                    gh.set_discussions_enabled(&repo_name, enabled).await?;
+                }
+                // Categories are only checked; the step fails until they are fixed by hand.
+                if let Some(diff) = &discussions_diff
+                    && diff.has_category_changes()
+                {
+                    return Err(Error::DiscussionCategoriesDiffer {
+                        repo: repo_name.clone(),
+                        count: diff.to_add.len() + diff.to_update.len() + diff.to_remove.len(),
+                    });
+                }
+                Ok(())
+            }
+            .await;
+            settle("updating discussions", result, failures, options.keep_going)?;
+
+            let result: Result<()> = async {
+                if let Some(milestones) = &milestone_diff {
+                    for milestone in &milestones.to_add {
+                        journal.record_unrestorable(
+                            Some(&repo_name),
+                            format!("created milestone {}", milestone.title),
+                        )?;
+                        gh.create_milestone(&repo_name, milestone).await?;
+                    }
+                    for (number, milestone) in &milestones.to_update {
+                        journal.record_unrestorable(
+                            Some(&repo_name),
+                            format!("updated milestone {}", milestone.title),
+                        )?;
+                        gh.update_milestone(&repo_name, *number, milestone).await?;
+                    }
+                    for milestone in &milestones.to_remove {
+                        journal.record_unrestorable(
+                            Some(&repo_name),
+                            format!("deleted milestone {}", milestone.spec.title),
+                        )?;
+                        gh.delete_milestone(&repo_name, milestone.number).await?;
                     }
                 }
+                Ok(())
+            }
+            .await;
+            settle("updating milestones", result, failures, options.keep_going)?;
 
+            let result: Result<()> = async {
                 for label in &diff.to_add {
+                    journal.record(&JournalEntry::Label {
+                        repo: repo_name.clone(),
+                        before: None,
+                        after: Some(label.clone()),
+                    })?;
                     gh.create_label(&repo_name, label).await?;
                 }
                 for label in &diff.to_update {
-                    gh.update_label(&repo_name, label).await?;
+                    journal.record(&JournalEntry::Label {
+                        repo: repo_name.clone(),
+                        before: current_labels
+                            .iter()
+                            .find(|l| l.name == label.name)
+                            .map(|l| LabelSpec {
+                                name: l.name.clone(),
+                                color: Some(l.color.clone()),
+                                description: l.description.clone(),
+                            }),
+                        after: Some(label.clone()),
+                    })?;
+                    gh.update_label(&repo_name, &label.name, label).await?;
                 }
                 for label in &removable {
+                    // Removed labels come from the current label list, so they are the before-state.
+                    journal.record(&JournalEntry::Label {
+                        repo: repo_name.clone(),
+                        before: Some(label.clone()),
+                        after: None,
+                    })?;
                     gh.delete_label(&repo_name, &label.name).await?;
                 }
                 if !blocked_removals.is_empty() {
@@ -290,42 +1387,223 @@ async fn handle_repos(
                         format_blocked_lines(&blocked_removals, verbose)
                     );
                 }
+                Ok(())
+            }
+            .await;
+            settle("updating labels", result, failures, options.keep_going)?;
 
+            // The PR is opened on the branch the files were written to, so these two steps run
+            // as one and a failure skips the rest.
+            let mut stage = "writing .github files";
+            let result: Result<String> = async {
+                let mut direct_note = None;
+                let mut pr_file_changes = any_file_changes;
+                let mut obsolete = obsolete_pr;
+                if direct && any_file_changes {
+                    let (changes, message) =
+                        file_commit(&templates_add, &templates_update, &templates_remove);
+                    match gh
+                        .commit_files(&repo_name, &base_branch, &changes, &message)
+                        .await
+                    {
+                        Ok(sha) => {
+                            // Recorded only once the commit landed: a rejected push falls back
+                            // to the PR branch, which is journaled on its own.
+                            journal.record(&JournalEntry::Files {
+                                repo: repo_name.clone(),
+                                branch: base_branch.clone(),
+                                files: files_before(
+                                    &templates_add,
+                                    &templates_update,
+                                    &templates_remove,
+                                    &previous_contents,
+                                ),
+                            })?;
+                            pr_file_changes = false;
+                            obsolete = existing_pr.is_some();
+                            direct_note = Some(format!(
+                                "committed directly to '{}' ({})",
+                                base_branch,
+                                &sha[..sha.len().min(7)]
+                            ));
+                        }
+                        Err(Error::ProtectedBranch { reason, .. }) => {
+                            direct_note = Some(format!(
+                                "direct commit to '{base_branch}' rejected ({reason}); fell back to a PR"
+                            ));
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                let mut cleanup_note = None;
+                let mut rebuild_from = None;
+                if obsolete && let Some(pr) = &existing_pr {
+                    let branch = &pr.head.ref_field;
+                    let comment = format!(
+                        "Closing because the .github files in this PR already match `{base_branch}`."
+                    );
+                    gh.close_pull_request(&repo_name, pr.number, &comment)
+                        .await?;
+                    gh.delete_branch(&repo_name, branch).await?;
+                    pr_file_changes = false;
+                    cleanup_note = Some(format!(
+                        "closed obsolete PR #{} and deleted branch '{branch}'",
+                        pr.number
+                    ));
+                } else if let (Some(pr), Some(reason)) = (&existing_pr, staleness) {
+                    let branch = &pr.head.ref_field;
+                    cleanup_note = Some(
+                        match merged_cfg.pull_request.stale_branch.unwrap_or_default() {
+                            StaleBranchAction::Recreate => {
+                                // The files are committed on top of the latest base further down.
+                                rebuild_from = Some(gh.get_branch_sha(&repo_name, &base_branch).await?);
+                                format!(
+                                    "recreated stale branch '{branch}' from '{base_branch}' ({reason})"
+                                )
+                            }
+                            StaleBranchAction::Reopen => {
+                                let comment = format!(
+                                    "Closing as stale: {reason} (base `{base_branch}`). gh-governor opens a fresh PR from the latest `{base_branch}` when changes remain."
+                                );
+                                gh.close_pull_request(&repo_name, pr.number, &comment)
+                                    .await?;
+                                gh.delete_branch(&repo_name, branch).await?;
+                                format!("closed stale PR #{} ({reason})", pr.number)
+                            }
+                        },
+                    );
+                } else if let (Some(pr), Some(reason)) = (&existing_pr, stale) {
+                    cleanup_note = Some(format!(
+                        "left stale branch '{}' as is ({reason}; {foreign_commits} commit(s) not made by gh-governor)",
+                        pr.head.ref_field
+                    ));
+                }
+
+                let existing_pr = if pr_file_changes || existing_pr.is_some() {
+                    gh.find_open_pr_by_head_prefix(&repo_name, PR_BRANCH_PREFIX, &base_branch)
+                        .await?
+                } else {
+                    None
+                };
+                let branch_name = if let Some(pr) = &existing_pr {
+                    Some(pr.head.ref_field.clone())
+                } else if pr_file_changes {
+                    let name = format!("{PR_BRANCH_PREFIX}{}", base_branch);
+                    let base_sha = gh.get_branch_sha(&repo_name, &base_branch).await?;
+                    gh.create_branch_from(&repo_name, &name, &base_sha).await?;
+                    Some(name)
+                } else {
+                    None
+                };
+
+                if let Some(branch_ref) = branch_name.as_deref()
+                    && pr_file_changes
+                {
+                    let (changes, message) =
+                        file_commit(&templates_add, &templates_update, &templates_remove);
+                    journal.record(&JournalEntry::Files {
+                        repo: repo_name.clone(),
+                        branch: branch_ref.to_string(),
+                        files: files_before(
+                            &templates_add,
+                            &templates_update,
+                            &templates_remove,
+                            &previous_contents,
+                        ),
+                    })?;
+                    match &rebuild_from {
+                        Some(base_sha) => {
+                            gh.rebuild_branch(&repo_name, branch_ref, base_sha, &changes, &message)
+                                .await?
+                        }
+                        None => {
+                            gh.commit_files(&repo_name, branch_ref, &changes, &message)
+                                .await?
+                        }
+                    };
+                }
+
+                stage = "opening the pull request";
                 let mut pr_status = "no PR (no .github file changes)".to_string();
                 if let Some(branch) = branch_name.as_deref() {
-                    let pr_title =
-                        format!("gh-governor updates ({})", Utc::now().format("%Y-%m-%d"));
-                    let pr_body = Some("Automated .github updates via gh-governor");
+                    let pr_title = format!("gh-governor updates ({})", Utc::now().format("%Y-%m-%d"));
+                    let body = pr_body(
+                        &templates_add,
+                        &templates_update,
+                        &templates_remove,
+                        &previous_contents,
+                        &merged_cfg.file_sources,
+                    );
                     let mut pr_opt = existing_pr;
-                    if pr_opt.is_none() && any_file_changes {
-                        gh.create_pull_request(
-                            &repo_name,
-                            &pr_title,
-                            branch,
-                            &base_branch,
-                            pr_body,
-                            true,
-                        )
-                        .await?;
-                        pr_opt = gh
-                            .find_open_pr_by_head_prefix(&repo_name, PR_BRANCH_PREFIX, &base_branch)
+                    // The PR opened earlier is still open, but its branch now carries this run's
+                    // changes, so its description has to follow.
+                    if let Some(pr) = &pr_opt
+                        && pr_file_changes
+                    {
+                        gh.update_pull_request(&repo_name, pr.number, &pr_title, Some(&body))
+                            .await?;
+                    }
+                    // The PR exists once it is created; decorating it is best effort and only
+                    // reported.
+                    let mut pr_warnings = Vec::new();
+                    if pr_opt.is_none() && pr_file_changes {
+                        let options = &merged_cfg.pull_request;
+                        let pr = gh
+                            .create_pull_request(
+                                &repo_name,
+                                &pr_title,
+                                branch,
+                                &base_branch,
+                                Some(&body),
+                                options.is_draft(),
+                            )
                             .await?;
+                        if let Err(e) = gh
+                            .decorate_pull_request(
+                                &repo_name,
+                                pr.number,
+                                &options.labels,
+                                &options.assignees,
+                                &options.reviewers,
+                                &options.team_reviewers,
+                            )
+                            .await
+                        {
+                            pr_warnings
+                                .push(format!("labels, assignees or reviewers not applied: {e}"));
+                        }
+                        if let Some(method) = options.auto_merge
+                            && !options.is_draft()
+                            && let Some(node_id) = pr.node_id.as_deref()
+                            && let Err(e) = gh.enable_auto_merge(&repo_name, node_id, method).await
+                        {
+                            pr_warnings.push(format!("auto-merge not enabled: {e}"));
+                        }
+                        pr_opt = Some(pr);
                     }
                     if let Some(pr) = pr_opt {
-                        let url =
-                            pr.html_url
-                                .as_ref()
-                                .map(|u| u.to_string())
-                                .unwrap_or_else(|| {
-                                    format!(
-                                        "https://github.com/{}/{}/pull/{}",
-                                        gh.org, repo_name, pr.number
-                                    )
-                                });
+                        let url = pr
+                            .html_url
+                            .as_ref()
+                            .map(|u| u.to_string())
+                            .unwrap_or_else(|| {
+                                format!(
+                                    "https://github.com/{}/{}/pull/{}",
+                                    gh.org, repo_name, pr.number
+                                )
+                            });
                         pr_status = format!(
-                            "draft PR #{} ({} -> {}) [{}]",
-                            pr.number, branch, base_branch, url
+                            "{} #{} ({} -> {}) [{}]",
+                            pr_kind(pr.draft.unwrap_or(false)),
+                            pr.number,
+                            branch,
+                            base_branch,
+                            url
                         );
+                        for warning in &pr_warnings {
+                            pr_status.push_str(&format!("; {warning}"));
+                        }
                     } else {
                         pr_status = format!(
                             "no PR created for branch '{}' (no changes to apply)",
@@ -334,63 +1612,542 @@ async fn handle_repos(
                     }
                 }
 
-                let (settings_count, settings_lines) = format_repo_settings(settings_diff.as_ref());
-                let (bp_count, bp_lines) = format_branch_protection(&bp_changes, verbose);
-                println!(
-                    "Repo {} (apply):\n  Repo settings changes ({}) :{}\n  Branch protection ({}) :{}\n  PR:\n    {}\n    .github files added ({}) :{}\n    .github files updated ({}) :{}\n    .github files removed ({}) :{}\n  Added labels ({}) :{}\n  Updated labels ({}) :{}\n  Removed labels ({}) :{}",
-                    repo_name,
-                    settings_count,
-                    settings_lines,
-                    bp_count,
-                    bp_lines,
-                    pr_status,
-                    format_count(templates_add.len(), ColorKind::Add),
-                    format_template_lines(&templates_add, ColorKind::Add),
-                    format_count(templates_update.len(), ColorKind::Update),
-                    format_template_lines(
-                        &templates_update
-                            .iter()
-                            .map(|(t, _)| t.clone())
-                            .collect::<Vec<_>>(),
-                        ColorKind::Update
-                    ),
-                    format_count(templates_remove.len(), ColorKind::Remove),
-                    format_remove_lines(&templates_remove),
-                    format_count(diff.to_add.len(), ColorKind::Add),
-                    format_label_lines(&diff.to_add, ColorKind::Add),
-                    format_count(diff.to_update.len(), ColorKind::Update),
-                    format_label_lines(&diff.to_update, ColorKind::Update),
-                    format_count(
-                        diff.to_remove.len() - blocked_removals.len(),
-                        ColorKind::Remove
-                    ),
-                    format_label_lines(&removable, ColorKind::Remove),
-                );
-            }
+                match direct_note {
+                    Some(note) if pr_file_changes => pr_status = format!("{note}; {pr_status}"),
+                    Some(note) => pr_status = note,
+                    None => {}
+                }
+                if let Some(note) = cleanup_note {
+                    pr_status = format!("{note}; {pr_status}");
+                }
+                Ok(pr_status)
+            }
+            .await;
+            let pr_status = settle(stage, result, failures, options.keep_going)?
+                .unwrap_or_else(|| format!("not updated (failed while {stage})"));
+
+            let (settings_count, settings_lines) = format_repo_settings(settings_diff.as_ref());
+            let (bp_count, bp_lines) = format_branch_protection(
+                protection_configured.then_some(bp_changes.as_slice()),
+                verbose,
+            );
+            let (access_count, access_lines) = format_access(access_diff.as_ref());
+            let (webhook_count, webhook_lines) = format_webhooks(webhook_diff.as_ref());
+            let (env_count, env_lines) = format_environments(environment_changes.as_deref());
+            let (autolink_count, autolink_lines) = format_autolinks(autolink_diff.as_ref());
+            let (property_count, property_lines) = format_properties(property_changes.as_deref());
+            let (milestone_count, milestone_lines) = format_milestones(milestone_diff.as_ref());
+            let (discussion_count, discussion_lines) =
+                format_discussions(discussions_diff.as_ref());
+            println!(
+                "Repo {} (apply):\n  Repo settings changes ({}) :{}\n  Branch protection ({}) :{}\n  Access ({}) :{}\n  Webhooks ({}) :{}\n  Environments ({}) :{}\n  Autolinks ({}) :{}\n  Custom properties ({}) :{}\n  Milestones ({}) :{}\n  Discussions ({}) :{}\n  PR:\n    {}\n    .github files added ({}) :{}\n    .github files updated ({}) :{}\n    .github files removed ({}) :{}\n  Added labels ({}) :{}\n  Updated labels ({}) :{}\n  Removed labels ({}) :{}",
+                repo_name,
+                settings_count,
+                settings_lines,
+                bp_count,
+                bp_lines,
+                access_count,
+                access_lines,
+                webhook_count,
+                webhook_lines,
+                env_count,
+                env_lines,
+                autolink_count,
+                autolink_lines,
+                property_count,
+                property_lines,
+                milestone_count,
+                milestone_lines,
+                discussion_count,
+                discussion_lines,
+                pr_status,
+                format_count(templates_add.len(), ColorKind::Add),
+                format_template_lines(&templates_add, ColorKind::Add),
+                format_count(templates_update.len(), ColorKind::Update),
+                format_template_lines(
+                    &templates_update
+                        .iter()
+                        .map(|(t, _)| t.clone())
+                        .collect::<Vec<_>>(),
+                    ColorKind::Update
+                ),
+                format_count(templates_remove.len(), ColorKind::Remove),
+                format_remove_lines(&templates_remove),
+                format_count(diff.to_add.len(), ColorKind::Add),
+                format_label_lines(&diff.to_add, ColorKind::Add),
+                format_count(diff.to_update.len(), ColorKind::Update),
+                format_label_lines(&diff.to_update, ColorKind::Update),
+                format_count(
+                    diff.to_remove.len() - blocked_removals.len(),
+                    ColorKind::Remove
+                ),
+                format_label_lines(&removable, ColorKind::Remove),
+            );
+        }
+    }
+    Ok(None)
+}
+
+#[derive(Clone, Copy)]
+enum ColorKind {
+    Add,
+    Update,
+    Remove,
+    Blocked,
+}
+
+#[derive(Clone)]
+struct BranchProtectionChange {
+    pattern: String,
+    action: ChangeAction,
+    target: BranchProtectionRule,
+    current: Option<BranchProtectionRule>,
+}
+
+#[derive(Clone, Copy)]
+enum ChangeAction {
+    Create,
+    Update,
+}
+
+/// `.github` file differences between the desired templates and a branch.
+#[derive(Debug, Default)]
+struct FileChanges {
+    add: Vec<IssueTemplateFile>,
+    update: Vec<(IssueTemplateFile, String)>,
+    /// (path, sha)
+    remove: Vec<(String, String)>,
+    /// Current contents of updated and removed files, keyed by path.
+    previous: HashMap<String, String>,
+}
+
+impl FileChanges {
+    fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.update.is_empty() && self.remove.is_empty()
+    }
+}
+
+/// Compares `desired` with the files on `branch` (the default branch when `None`). Unmanaged
+/// templates are only reported for removal when a branch is given.
+async fn compute_file_changes(
+    gh: &GithubClient,
+    repo_name: &str,
+    desired: &[IssueTemplateFile],
+    branch: Option<&str>,
+) -> Result<FileChanges> {
+    let mut changes = FileChanges::default();
+    for tpl in desired {
+        match gh.get_file(repo_name, &tpl.path, branch).await? {
+            None => changes.add.push(tpl.clone()),
+            Some(file) => {
+                // Only the keys gh-governor generates are rewritten in config.yml, so it is
+                // compared structurally; every other file must match byte for byte.
+                let is_config = short_github_path(&tpl.path) == ISSUE_CONFIG_PATH;
+                let contents = if is_config {
+                    update_top_level_keys(&file.content, &tpl.contents)
+                } else {
+                    tpl.contents.clone()
+                };
+                let unchanged = if is_config {
+                    yaml_equivalent(&file.content, &contents)
+                } else {
+                    file.content == contents
+                };
+                if !unchanged {
+                    let path = tpl.path.clone();
+                    changes.previous.insert(path.clone(), file.content);
+                    changes
+                        .update
+                        .push((IssueTemplateFile { path, contents }, file.sha));
+                }
+            }
+        }
+    }
+    if let Some(branch_ref) = branch {
+        let current_paths = gh
+            .list_github_files(repo_name, branch_ref, ".github/")
+            .await
+            .unwrap_or_default();
+        for path in current_paths {
+            if TEMPLATE_DIRS
+                .iter()
+                .any(|dir| path.starts_with(&format!(".github/{dir}/")))
+                && !desired.iter().any(|t| short_github_path(&t.path) == path)
+                && let Some(file) = gh.get_file(repo_name, &path, branch).await?
+            {
+                changes.previous.insert(path.clone(), file.content);
+                changes.remove.push((path, file.sha));
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Before-state of every file in a batched commit, for the apply journal.
+fn files_before(
+    add: &[IssueTemplateFile],
+    update: &[(IssueTemplateFile, String)],
+    remove: &[(String, String)],
+    previous: &HashMap<String, String>,
+) -> Vec<FileBefore> {
+    let existing = |path: String, sha: &String| FileBefore {
+        contents: previous.get(&path).cloned(),
+        sha: Some(sha.clone()),
+        path,
+    };
+    add.iter()
+        .map(|t| FileBefore {
+            path: short_github_path(&t.path),
+            sha: None,
+            contents: None,
+        })
+        .chain(
+            update
+                .iter()
+                .map(|(t, sha)| existing(short_github_path(&t.path), sha)),
+        )
+        .chain(remove.iter().map(|(path, sha)| existing(path.clone(), sha)))
+        .collect()
+}
+
+/// Builds the changes and message for the single commit carrying all .github file changes.
+fn file_commit(
+    add: &[IssueTemplateFile],
+    update: &[(IssueTemplateFile, String)],
+    remove: &[(String, String)],
+) -> (Vec<FileChange>, String) {
+    let mut changes = Vec::new();
+    let mut lines = Vec::new();
+    for tpl in add {
+        let path = short_github_path(&tpl.path);
+        lines.push(format!("- Add {path}"));
+        changes.push(FileChange {
+            path,
+            contents: Some(tpl.contents.clone()),
+        });
+    }
+    for (tpl, _) in update {
+        let path = short_github_path(&tpl.path);
+        lines.push(format!("- Update {path}"));
+        changes.push(FileChange {
+            path,
+            contents: Some(tpl.contents.clone()),
+        });
+    }
+    for (path, _) in remove {
+        lines.push(format!("- Remove {path}"));
+        changes.push(FileChange {
+            path: path.clone(),
+            contents: None,
+        });
+    }
+    let message = format!("{COMMIT_SUBJECT}\n\n{}", lines.join("\n"));
+    (changes, message)
+}
+
+/// Lines of unified diff shown per file in the PR body before it is cut off.
+const PR_DIFF_MAX_LINES: usize = 40;
+
+/// Whether a classic protection rule on the default branch would reject a direct push.
+/// Rulesets are not checked here; apply still falls back to a PR if they reject it.
+fn blocks_direct_push(rule: &BranchProtectionRule) -> bool {
+    rule.required_pull_request_reviews.is_some()
+        || rule.required_status_checks.is_some()
+        || rule.restrictions.is_some()
+}
+
+fn pr_kind(draft: bool) -> &'static str {
+    if draft { "draft PR" } else { "PR" }
+}
+
+/// PR description listing each changed file, the set it comes from and a short diff.
+fn pr_body(
+    add: &[IssueTemplateFile],
+    update: &[(IssueTemplateFile, String)],
+    remove: &[(String, String)],
+    previous: &HashMap<String, String>,
+    sources: &BTreeMap<String, String>,
+) -> String {
+    let source = |path: &str| match sources.get(path) {
+        Some(set) => format!("set `{set}`"),
+        None if path == ISSUE_CONFIG_PATH => "generated from issue templates".to_string(),
+        None => "no longer provided by any set".to_string(),
+    };
+    let mut out = String::from("Automated .github updates via gh-governor.\n");
+    let empty = String::new();
+    let files = add
+        .iter()
+        .map(|t| ("Add", t.path.as_str(), t.contents.as_str()))
+        .chain(
+            update
+                .iter()
+                .map(|(t, _)| ("Update", t.path.as_str(), t.contents.as_str())),
+        )
+        .chain(remove.iter().map(|(p, _)| ("Remove", p.as_str(), "")));
+    for (action, path, new) in files {
+        let path = short_github_path(path);
+        let old = previous.get(&path).unwrap_or(&empty);
+        out.push_str(&format!(
+            "\n### {action} `{path}`\n\nSource: {}\n\n```diff\n{}```\n",
+            source(&path),
+            unified_diff_lines(&path, old, new, PR_DIFF_MAX_LINES)
+                .iter()
+                .map(|l| format!("{l}\n"))
+                .collect::<String>()
+        ));
+    }
+    out
+}
+
+/// Unified diff between `old` and `new`, cut off after `max_lines` lines (0 keeps all).
+fn unified_diff_lines(path: &str, old: &str, new: &str, max_lines: usize) -> Vec<String> {
+    let diff = TextDiff::from_lines(old, new);
+    let text = diff
+        .unified_diff()
+        .context_radius(2)
+        .header(&format!("a/{path}"), &format!("b/{path}"))
+        .to_string();
+    let mut lines: Vec<String> = text.lines().map(|l| l.to_string()).collect();
+    if max_lines > 0 && lines.len() > max_lines {
+        let hidden = lines.len() - max_lines;
+        lines.truncate(max_lines);
+        lines.push(format!("... {hidden} more lines"));
+    }
+    lines
+}
+
+/// Colored diffs for `plan --show-diff`: the full contents of added files and a unified
+/// diff against the current contents for updated ones.
+fn format_file_diffs(
+    add: &[IssueTemplateFile],
+    update: &[(IssueTemplateFile, String)],
+    previous: &HashMap<String, String>,
+    max_lines: usize,
+) -> String {
+    let empty = String::new();
+    let files = add
+        .iter()
+        .map(|t| (t, &empty))
+        .chain(update.iter().map(|(t, _)| {
+            let path = short_github_path(&t.path);
+            (t, previous.get(&path).unwrap_or(&empty))
+        }));
+    let mut out = String::new();
+    for (tpl, old) in files {
+        let path = short_github_path(&tpl.path);
+        out.push_str(&format!("\n    {path}"));
+        for line in unified_diff_lines(&path, old, &tpl.contents, max_lines) {
+            let line = if line.starts_with("+++") || line.starts_with("---") {
+                line
+            } else if line.starts_with('+') {
+                apply_color(&line, ColorKind::Add)
+            } else if line.starts_with('-') {
+                apply_color(&line, ColorKind::Remove)
+            } else if line.starts_with("@@") {
+                apply_color(&line, ColorKind::Update)
+            } else {
+                line
+            };
+            out.push_str(&format!("\n      {line}"));
+        }
+    }
+    out
+}
+
+/// Report section built from a category's changes; `None` when the repo does not configure
+/// the category. Blocked entries are listed in the details without counting as changes.
+fn report_section(name: &'static str, changes: Option<(usize, Vec<String>)>) -> ReportSection {
+    let (count, details) = match changes {
+        Some((count, details)) => (Some(count), details),
+        None => (None, Vec::new()),
+    };
+    ReportSection {
+        name,
+        count,
+        details,
+    }
+}
+
+fn setting_change_details(changes: &[SettingChange]) -> Vec<String> {
+    changes
+        .iter()
+        .map(|c| {
+            format!(
+                "- `{}`: {} -> {}",
+                c.field,
+                c.current.as_deref().unwrap_or("unset"),
+                c.desired
+            )
+        })
+        .collect()
+}
+
+fn settings_report(diff: &RepoSettingsDiff) -> (usize, Vec<String>) {
+    let mut details = setting_change_details(&diff.changes);
+    details.extend(diff.unavailable.iter().map(|field| {
+        format!("- `{field}`: not available for this repository (plan or permissions)")
+    }));
+    (diff.changes.len(), details)
+}
+
+fn protection_report(changes: &[BranchProtectionChange]) -> (usize, Vec<String>) {
+    let details = changes
+        .iter()
+        .map(|c| {
+            let action = match c.action {
+                ChangeAction::Create => "create",
+                ChangeAction::Update => "update",
+            };
+            format!("- {action} rule `{}`", c.pattern)
+        })
+        .collect();
+    (changes.len(), details)
+}
+
+fn access_report(diff: &AccessDiff) -> (usize, Vec<String>) {
+    let groups = [
+        ("add", &diff.to_add),
+        ("update", &diff.to_update),
+        ("remove", &diff.to_remove),
+    ];
+    let mut details = Vec::new();
+    for (verb, changes) in groups {
+        for change in changes {
+            let who = access_kind_name(change.kind);
+            details.push(format!(
+                "- {verb} {who} `{}`: {} -> {}",
+                change.name,
+                change.current.map(|p| p.as_str()).unwrap_or("none"),
+                change.desired.map(|p| p.as_str()).unwrap_or("removed")
+            ));
+        }
+    }
+    (details.len(), details)
+}
+
+fn webhooks_report(diff: &WebhookDiff) -> (usize, Vec<String>) {
+    let mut details: Vec<String> = diff
+        .to_add
+        .iter()
+        .map(|hook| format!("- add `{}`", hook.url))
+        .collect();
+    for update in &diff.to_update {
+        let mut line = format!("- update `{}`", update.spec.url);
+        if update.loses_secret() {
+            line.push_str(" (blocked: config change would drop the current secret)");
         }
+        details.push(line);
     }
-    Ok(())
+    details.extend(
+        diff.to_remove
+            .iter()
+            .map(|(_, url)| format!("- remove `{url}`")),
+    );
+    (details.len(), details)
 }
 
-#[derive(Clone, Copy)]
-enum ColorKind {
-    Add,
-    Update,
-    Remove,
-    Blocked,
+fn environments_report(changes: &[EnvironmentChange]) -> (usize, Vec<String>) {
+    let details = changes
+        .iter()
+        .map(|c| {
+            if c.exists {
+                format!("- update `{}` ({})", c.spec.name, c.fields.join(", "))
+            } else {
+                format!("- create `{}`", c.spec.name)
+            }
+        })
+        .collect();
+    (changes.len(), details)
 }
 
-#[derive(Clone)]
-struct BranchProtectionChange {
-    pattern: String,
-    action: ChangeAction,
-    target: BranchProtectionRule,
+fn autolinks_report(diff: &AutolinkDiff) -> (usize, Vec<String>) {
+    let mut details: Vec<String> = diff
+        .to_add
+        .iter()
+        .map(|l| format!("- add `{}` -> {}", l.key_prefix, l.url_template))
+        .collect();
+    details.extend(
+        diff.to_replace
+            .iter()
+            .map(|(_, l)| format!("- recreate `{}` -> {}", l.key_prefix, l.url_template)),
+    );
+    details.extend(
+        diff.to_remove
+            .iter()
+            .map(|(_, l)| format!("- remove `{}`", l.key_prefix)),
+    );
+    (details.len(), details)
 }
 
-#[derive(Clone, Copy)]
-enum ChangeAction {
-    Create,
-    Update,
+fn properties_report(changes: &[PropertyChange]) -> (usize, Vec<String>) {
+    let details = changes
+        .iter()
+        .map(|c| match &c.current {
+            Some(current) => format!("- `{}`: {} -> {}", c.name, current, c.desired),
+            None => format!("- `{}`: unset -> {}", c.name, c.desired),
+        })
+        .collect();
+    (changes.len(), details)
+}
+
+fn milestones_report(diff: &MilestoneDiff) -> (usize, Vec<String>) {
+    let mut details: Vec<String> = diff
+        .to_add
+        .iter()
+        .map(|m| format!("- add `{}`", m.title))
+        .collect();
+    details.extend(
+        diff.to_update
+            .iter()
+            .map(|(_, m)| format!("- update `{}`", m.title)),
+    );
+    details.extend(
+        diff.to_remove
+            .iter()
+            .map(|m| format!("- remove `{}`", m.spec.title)),
+    );
+    let count = details.len();
+    details.extend(diff.blocked.iter().map(|m| {
+        format!(
+            "- blocked removal of `{}`, still has {} open issues",
+            m.spec.title, m.open_issues
+        )
+    }));
+    (count, details)
+}
+
+fn discussions_report(diff: &DiscussionsDiff) -> (usize, Vec<String>) {
+    let mut details = Vec::new();
+    if let Some(enabled) = diff.enable {
+        details.push(if enabled {
+            "- enable discussions".to_string()
+        } else {
+            "- disable discussions".to_string()
+        });
+    }
+    details.extend(
+        diff.to_add
+            .iter()
+            .map(|c| format!("- add category `{}`", c.name)),
+    );
+    details.extend(
+        diff.to_update
+            .iter()
+            .map(|(c, fields)| format!("- update category `{}` ({})", c.name, fields.join(", "))),
+    );
+    details.extend(
+        diff.to_remove
+            .iter()
+            .map(|c| format!("- remove category `{}`", c.spec.name)),
+    );
+    let count = details.len();
+    details.extend(diff.blocked.iter().map(|c| {
+        format!(
+            "- blocked removal of category `{}`, still has {} discussions",
+            c.spec.name, c.discussions
+        )
+    }));
+    (count, details)
 }
 
 fn format_count(count: usize, kind: ColorKind) -> String {
@@ -495,31 +2252,382 @@ fn format_blocked_lines(blocked: &[(LabelSpec, Vec<LabelUsageEntry>)], verbose:
 fn format_repo_settings(diff: Option<&RepoSettingsDiff>) -> (String, String) {
     match diff {
         None => ("not configured".to_string(), " not configured".to_string()),
-        Some(d) if d.changes.is_empty() => ("0".to_string(), " none".to_string()),
+        Some(d) if d.changes.is_empty() && d.unavailable.is_empty() => {
+            ("0".to_string(), " none".to_string())
+        }
         Some(d) => {
-            let mut out = String::new();
-            for change in &d.changes {
-                let line = format!(
-                    "    - {}: {} -> {}",
-                    change.field,
-                    change
-                        .current
-                        .clone()
-                        .unwrap_or_else(|| "unset".to_string()),
-                    apply_color(&change.desired, ColorKind::Update)
-                );
+            let mut out = format_setting_changes(&d.changes);
+            for field in &d.unavailable {
                 out.push('\n');
-                out.push_str(&line);
+                out.push_str(&format!(
+                    "    - {}: {}",
+                    field,
+                    apply_color(
+                        "not available for this repository (plan or permissions)",
+                        ColorKind::Blocked
+                    )
+                ));
             }
             (format_count(d.changes.len(), ColorKind::Update), out)
         }
     }
 }
 
-fn format_branch_protection(changes: &[BranchProtectionChange], verbose: bool) -> (String, String) {
-    if changes.is_empty() {
+fn format_setting_changes(changes: &[SettingChange]) -> String {
+    let mut out = String::new();
+    for change in changes {
+        let line = format!(
+            "    - {}: {} -> {}",
+            change.field,
+            change
+                .current
+                .clone()
+                .unwrap_or_else(|| "unset".to_string()),
+            apply_color(&change.desired, ColorKind::Update)
+        );
+        out.push('\n');
+        out.push_str(&line);
+    }
+    out
+}
+
+fn format_access(diff: Option<&AccessDiff>) -> (String, String) {
+    let diff = match diff {
+        None => return ("not configured".to_string(), " not configured".to_string()),
+        Some(d) if d.is_empty() => return ("0".to_string(), " none".to_string()),
+        Some(d) => d,
+    };
+    let mut out = String::new();
+    let groups = [
+        (&diff.to_add, ColorKind::Add),
+        (&diff.to_update, ColorKind::Update),
+        (&diff.to_remove, ColorKind::Remove),
+    ];
+    for (changes, kind) in groups {
+        for change in changes {
+            let who = access_kind_name(change.kind);
+            out.push('\n');
+            out.push_str(&format!(
+                "    - {} {}: {} -> {}",
+                who,
+                apply_color(&change.name, kind),
+                change.current.map(|p| p.as_str()).unwrap_or("none"),
+                change.desired.map(|p| p.as_str()).unwrap_or("removed")
+            ));
+        }
+    }
+    let count = diff.to_add.len() + diff.to_update.len() + diff.to_remove.len();
+    (format_count(count, ColorKind::Update), out)
+}
+
+fn format_webhooks(diff: Option<&WebhookDiff>) -> (String, String) {
+    let diff = match diff {
+        None => return ("not configured".to_string(), " not configured".to_string()),
+        Some(d) if d.is_empty() => return ("0".to_string(), " none".to_string()),
+        Some(d) => d,
+    };
+    let mut out = String::new();
+    let describe = |hook: &WebhookSpec, kind: ColorKind| {
+        let mut line = format!("    - {}", apply_color(&hook.url, kind));
+        if let Some(events) = &hook.events {
+            line.push_str(&format!(" [{}]", events.join(", ")));
+        }
+        if hook.active == Some(false) {
+            line.push_str(" (inactive)");
+        }
+        if let Some(var) = &hook.secret_env
+            && std::env::var(var).is_err()
+        {
+            line.push_str(&format!(
+                " {}",
+                apply_color(
+                    &format!("secret env '{var}' is not set"),
+                    ColorKind::Blocked
+                )
+            ));
+        }
+        line
+    };
+    for hook in &diff.to_add {
+        out.push('\n');
+        out.push_str(&describe(hook, ColorKind::Add));
+    }
+    for update in &diff.to_update {
+        out.push('\n');
+        out.push_str(&describe(&update.spec, ColorKind::Update));
+        if update.loses_secret() {
+            out.push_str(&format!(
+                " {}",
+                apply_color(
+                    "config change would drop the current secret; set secret_env",
+                    ColorKind::Blocked
+                )
+            ));
+        }
+    }
+    for (_, url) in &diff.to_remove {
+        out.push('\n');
+        out.push_str(&format!("    - {}", apply_color(url, ColorKind::Remove)));
+    }
+    let count = diff.to_add.len() + diff.to_update.len() + diff.to_remove.len();
+    (format_count(count, ColorKind::Update), out)
+}
+
+fn format_environments(changes: Option<&[EnvironmentChange]>) -> (String, String) {
+    let changes = match changes {
+        None => return ("not configured".to_string(), " not configured".to_string()),
+        Some([]) => return ("0".to_string(), " none".to_string()),
+        Some(c) => c,
+    };
+    let mut out = String::new();
+    for change in changes {
+        out.push('\n');
+        if change.exists {
+            out.push_str(&format!(
+                "    - {}: update ({})",
+                apply_color(&change.spec.name, ColorKind::Update),
+                change.fields.join(", ")
+            ));
+        } else {
+            out.push_str(&format!(
+                "    - {}: create",
+                apply_color(&change.spec.name, ColorKind::Add)
+            ));
+        }
+    }
+    (format_count(changes.len(), ColorKind::Update), out)
+}
+
+fn format_autolinks(diff: Option<&AutolinkDiff>) -> (String, String) {
+    let diff = match diff {
+        None => return ("not configured".to_string(), " not configured".to_string()),
+        Some(d) if d.is_empty() => return ("0".to_string(), " none".to_string()),
+        Some(d) => d,
+    };
+    let mut out = String::new();
+    for link in &diff.to_add {
+        out.push('\n');
+        out.push_str(&format!(
+            "    - {} -> {}",
+            apply_color(&link.key_prefix, ColorKind::Add),
+            link.url_template
+        ));
+    }
+    for (_, link) in &diff.to_replace {
+        out.push('\n');
+        out.push_str(&format!(
+            "    - {} -> {} (recreate)",
+            apply_color(&link.key_prefix, ColorKind::Update),
+            link.url_template
+        ));
+    }
+    for (_, link) in &diff.to_remove {
+        out.push('\n');
+        out.push_str(&format!(
+            "    - {}",
+            apply_color(&link.key_prefix, ColorKind::Remove)
+        ));
+    }
+    let count = diff.to_add.len() + diff.to_replace.len() + diff.to_remove.len();
+    (format_count(count, ColorKind::Update), out)
+}
+
+fn format_discussions(diff: Option<&DiscussionsDiff>) -> (String, String) {
+    let diff = match diff {
+        None => return ("not configured".to_string(), " not configured".to_string()),
+        Some(d) if d.is_empty() => return ("0".to_string(), " none".to_string()),
+        Some(d) => d,
+    };
+    let category = |c: &DiscussionCategorySpec, kind: ColorKind| {
+        let name = match &c.emoji {
+            Some(emoji) => format!("{emoji} {}", c.name),
+            None => c.name.clone(),
+        };
+        apply_color(&name, kind)
+    };
+    let mut out = String::new();
+    if let Some(enabled) = diff.enable {
+        out.push_str(&format!(
+            "\n    - {}",
+            apply_color(
+                if enabled {
+                    "enable discussions"
+                } else {
+                    "disable discussions"
+                },
+                ColorKind::Update
+            )
+        ));
+    }
+    for c in &diff.to_add {
+        out.push_str(&format!("\n    - {}", category(c, ColorKind::Add)));
+    }
+    for (c, fields) in &diff.to_update {
+        out.push_str(&format!(
+            "\n    - {} ({})",
+            category(c, ColorKind::Update),
+            fields.join(", ")
+        ));
+    }
+    for c in &diff.to_remove {
+        out.push_str(&format!("\n    - {}", category(&c.spec, ColorKind::Remove)));
+    }
+    for c in &diff.blocked {
+        out.push_str(&format!(
+            "\n    - {} {}",
+            category(&c.spec, ColorKind::Blocked),
+            apply_color(
+                &format!("(removal blocked: {} discussions)", c.discussions),
+                ColorKind::Blocked
+            )
+        ));
+    }
+    if diff.has_category_changes() {
+        out.push_str(&format!(
+            "\n    {}",
+            apply_color(
+                "category changes are manual and apply fails until they are made: GitHub has no API for managing categories",
+                ColorKind::Blocked
+            )
+        ));
+    }
+    let count = usize::from(diff.enable.is_some())
+        + diff.to_add.len()
+        + diff.to_update.len()
+        + diff.to_remove.len();
+    (format_count(count, ColorKind::Update), out)
+}
+
+fn format_milestones(diff: Option<&MilestoneDiff>) -> (String, String) {
+    let diff = match diff {
+        None => return ("not configured".to_string(), " not configured".to_string()),
+        Some(d) => d,
+    };
+    let describe = |m: &MilestoneSpec| {
+        let mut details = Vec::new();
+        if let Some(due_on) = &m.due_on {
+            details.push(format!("due {due_on}"));
+        }
+        if m.state == Some(MilestoneState::Closed) {
+            details.push("closed".to_string());
+        }
+        if details.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", details.join(", "))
+        }
+    };
+    let mut out = String::new();
+    for milestone in &diff.to_add {
+        out.push_str(&format!(
+            "\n    - {}{}",
+            apply_color(&milestone.title, ColorKind::Add),
+            describe(milestone)
+        ));
+    }
+    for (_, milestone) in &diff.to_update {
+        out.push_str(&format!(
+            "\n    - {}{}",
+            apply_color(&milestone.title, ColorKind::Update),
+            describe(milestone)
+        ));
+    }
+    for milestone in &diff.to_remove {
+        out.push_str(&format!(
+            "\n    - {}",
+            apply_color(&milestone.spec.title, ColorKind::Remove)
+        ));
+    }
+    for milestone in &diff.blocked {
+        out.push_str(&format!(
+            "\n    - {} {}",
+            apply_color(&milestone.spec.title, ColorKind::Blocked),
+            apply_color(
+                &format!("(removal blocked: {} open issues)", milestone.open_issues),
+                ColorKind::Blocked
+            )
+        ));
+    }
+    if out.is_empty() {
         return ("0".to_string(), " none".to_string());
     }
+    let count = diff.to_add.len() + diff.to_update.len() + diff.to_remove.len();
+    (format_count(count, ColorKind::Update), out)
+}
+
+fn format_properties(changes: Option<&[PropertyChange]>) -> (String, String) {
+    let changes = match changes {
+        None => return ("not configured".to_string(), " not configured".to_string()),
+        Some([]) => return ("0".to_string(), " none".to_string()),
+        Some(c) => c,
+    };
+    let mut out = String::new();
+    for change in changes {
+        out.push('\n');
+        match &change.current {
+            Some(current) => out.push_str(&format!(
+                "    - {}: {} -> {}",
+                apply_color(&change.name, ColorKind::Update),
+                current,
+                change.desired
+            )),
+            None => out.push_str(&format!(
+                "    - {}: {}",
+                apply_color(&change.name, ColorKind::Add),
+                change.desired
+            )),
+        }
+    }
+    (format_count(changes.len(), ColorKind::Update), out)
+}
+
+fn resolve_webhook_secret(hook: &WebhookSpec) -> Result<Option<String>> {
+    match &hook.secret_env {
+        Some(var) => std::env::var(var)
+            .map(Some)
+            .map_err(|_| crate::error::Error::MissingEnv(var.clone())),
+        None => Ok(None),
+    }
+}
+
+/// Keeps only the security features whose change was detected, so unchanged or unavailable
+/// features are not sent to GitHub.
+fn pending_security_changes(
+    desired: &SecuritySettings,
+    diff: &RepoSettingsDiff,
+) -> SecuritySettings {
+    let changed = |field: &str| diff.changes.iter().any(|c| c.field == field);
+    SecuritySettings {
+        advanced_security: desired
+            .advanced_security
+            .filter(|_| changed("security.advanced_security")),
+        secret_scanning: desired
+            .secret_scanning
+            .filter(|_| changed("security.secret_scanning")),
+        secret_scanning_push_protection: desired
+            .secret_scanning_push_protection
+            .filter(|_| changed("security.secret_scanning_push_protection")),
+        dependabot_security_updates: desired
+            .dependabot_security_updates
+            .filter(|_| changed("security.dependabot_security_updates")),
+        vulnerability_alerts: desired
+            .vulnerability_alerts
+            .filter(|_| changed("security.vulnerability_alerts")),
+        automated_security_fixes: desired
+            .automated_security_fixes
+            .filter(|_| changed("security.automated_security_fixes")),
+    }
+}
+
+fn format_branch_protection(
+    changes: Option<&[BranchProtectionChange]>,
+    verbose: bool,
+) -> (String, String) {
+    let changes = match changes {
+        None => return ("not configured".to_string(), " not configured".to_string()),
+        Some([]) => return ("0".to_string(), " none".to_string()),
+        Some(c) => c,
+    };
     let mut out = String::new();
     for change in changes {
         let action = match change.action {
@@ -688,6 +2796,10 @@ fn short_github_path(path: &str) -> String {
 }
 
 fn build_issue_template_config(templates: &[IssueTemplateFile]) -> Option<IssueTemplateFile> {
+    let templates: Vec<&IssueTemplateFile> = templates
+        .iter()
+        .filter(|t| short_github_path(&t.path).starts_with(".github/ISSUE_TEMPLATE/"))
+        .collect();
     let base = templates
         .iter()
         .find(|t| short_github_path(&t.path).ends_with("config.yml"));
@@ -695,11 +2807,13 @@ fn build_issue_template_config(templates: &[IssueTemplateFile]) -> Option<IssueT
     let desired_templates: Vec<IssueTemplateEntry> = templates
         .iter()
         .filter(|t| !short_github_path(&t.path).ends_with("config.yml"))
-        .map(|tpl| IssueTemplateEntry {
-            name: parse_template_name(&tpl.contents)
-                .or_else(|| file_stem(&tpl.path).map(|s| s.to_string())),
-            description: parse_template_description(&tpl.contents),
-            file: file_name(&tpl.path).unwrap_or_else(|| tpl.path.clone()),
+        .map(|tpl| {
+            let (name, description) = template_summary(&tpl.path, &tpl.contents);
+            IssueTemplateEntry {
+                name: name.or_else(|| file_stem(&tpl.path).map(|s| s.to_string())),
+                description,
+                file: file_name(&tpl.path).unwrap_or_else(|| tpl.path.clone()),
+            }
         })
         .collect();
 
@@ -714,23 +2828,11 @@ fn build_issue_template_config(templates: &[IssueTemplateFile]) -> Option<IssueT
 
     let contents = serde_yaml::to_string(&config).unwrap_or_default();
     Some(IssueTemplateFile {
-        path: ".github/ISSUE_TEMPLATE/config.yml".to_string(),
+        path: ISSUE_CONFIG_PATH.to_string(),
         contents,
     })
 }
 
-fn parse_template_name(contents: &str) -> Option<String> {
-    serde_yaml::from_str::<TemplateFrontMatter>(contents)
-        .ok()
-        .and_then(|f| f.name)
-}
-
-fn parse_template_description(contents: &str) -> Option<String> {
-    serde_yaml::from_str::<TemplateFrontMatter>(contents)
-        .ok()
-        .and_then(|f| f.description)
-}
-
 fn file_name(path: &str) -> Option<String> {
     Path::new(path)
         .file_name()
@@ -774,17 +2876,14 @@ struct IssueTemplateEntry {
     file: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct TemplateFrontMatter {
-    name: Option<String>,
-    description: Option<String>,
-}
-
+/// Merges the sets of every selected repository. With `keep_going`, a repository whose
+/// configuration fails to load or merge is returned with its error instead of ending the run.
 fn prepare_merged(
     root: &crate::config::RootConfig,
     sets_dir: &PathBuf,
     only_repos: &[String],
-) -> Result<Vec<(String, MergedRepoConfig)>> {
+    keep_going: bool,
+) -> Result<Vec<(String, Result<MergedRepoConfig>)>> {
     let mut set_cache: HashMap<String, SetDefinition> = HashMap::new();
     let mut merged = Vec::new();
 
@@ -792,44 +2891,90 @@ fn prepare_merged(
         if !only_repos.is_empty() && !only_repos.contains(&repo.name) {
             continue;
         }
-
-        let mut set_defs = Vec::new();
-        for set_name in root.default_sets.iter().chain(repo.sets.iter()) {
-            if !set_cache.contains_key(set_name) {
-                let loaded = crate::sets::load_set(sets_dir, set_name)?;
-                set_cache.insert(set_name.clone(), loaded);
-            }
-            let cached = set_cache
-                .get(set_name)
-                .expect("set should be loaded")
-                .clone();
-            set_defs.push(cached);
+        match merge_repo(root, sets_dir, repo, &mut set_cache) {
+            Ok(Some(m)) => merged.push((repo.name.clone(), Ok(m))),
+            Ok(None) => {}
+            Err(err) if keep_going => merged.push((repo.name.clone(), Err(err))),
+            Err(err) => return Err(err),
         }
+    }
 
-        if set_defs.is_empty() {
-            info!("repo '{}' has no configuration sets assigned", repo.name);
-            continue;
-        }
+    Ok(merged)
+}
 
-        if let Err(reason) = detect_template_conflicts(&set_defs) {
-            return Err(crate::error::Error::MergeConflict {
-                repo: repo.name.clone(),
-                reason,
-            });
+/// Merged configuration of one repository; `None` when it has nothing assigned.
+fn merge_repo(
+    root: &crate::config::RootConfig,
+    sets_dir: &Path,
+    repo: &crate::config::RepoConfig,
+    set_cache: &mut HashMap<String, SetDefinition>,
+) -> Result<Option<MergedRepoConfig>> {
+    let mut set_defs = Vec::new();
+    for set_name in root.default_sets.iter().chain(repo.sets.iter()) {
+        if !set_cache.contains_key(set_name) {
+            let loaded = crate::sets::load_set(sets_dir, set_name)?;
+            set_cache.insert(set_name.clone(), loaded);
         }
+        let cached = set_cache
+            .get(set_name)
+            .expect("set should be loaded")
+            .clone();
+        set_defs.push(cached);
+    }
+
+    if set_defs.is_empty() && repo.properties.is_empty() {
+        info!("repo '{}' has no configuration sets assigned", repo.name);
+        return Ok(None);
+    }
+
+    check_form_labels(&set_defs)?;
+
+    if let Err(reason) = detect_template_conflicts(&set_defs) {
+        return Err(crate::error::Error::MergeConflict {
+            repo: repo.name.clone(),
+            reason,
+        });
+    }
+
+    let mut m =
+        merge_sets_for_repo(&set_defs).map_err(|err| crate::error::Error::MergeConflict {
+            repo: repo.name.clone(),
+            reason: err.to_string(),
+        })?;
+    if repo.file_delivery.is_some() {
+        m.file_delivery = repo.file_delivery;
+    }
+    m.pull_request = root
+        .pull_request
+        .clone()
+        .unwrap_or_default()
+        .overlay(&repo.pull_request.clone().unwrap_or_default());
+    if !repo.properties.is_empty() {
+        m.properties
+            .get_or_insert_with(Default::default)
+            .extend(repo.properties.clone());
+    }
+    Ok(Some(m))
+}
 
-        match merge_sets_for_repo(&set_defs) {
-            Ok(m) => merged.push((repo.name.clone(), m)),
-            Err(err) => {
-                return Err(crate::error::Error::MergeConflict {
-                    repo: repo.name.clone(),
-                    reason: err.to_string(),
+/// Issue templates may only apply labels that the repo's merged label set defines.
+fn check_form_labels(sets: &[SetDefinition]) -> Result<()> {
+    let defined: Vec<String> = sets
+        .iter()
+        .flat_map(|s| s.labels.iter().map(|l| l.name.to_lowercase()))
+        .collect();
+    for set in sets {
+        for (path, labels) in &set.template_labels {
+            if let Some(missing) = labels.iter().find(|l| !defined.contains(&l.to_lowercase())) {
+                return Err(crate::error::Error::InvalidIssueTemplate {
+                    set: set.name.clone(),
+                    path: path.clone(),
+                    reason: format!("label '{missing}' is not defined in the merged label set"),
                 });
             }
         }
     }
-
-    Ok(merged)
+    Ok(())
 }
 
 fn detect_template_conflicts(sets: &[SetDefinition]) -> std::result::Result<(), String> {
@@ -865,8 +3010,17 @@ mod tests {
                 path: path.to_string(),
                 contents: contents.to_string(),
             }],
+            template_labels: BTreeMap::new(),
             repo_settings: None,
             checks: None,
+            access: None,
+            webhooks: None,
+            environments: None,
+            autolinks: None,
+            properties: None,
+            milestones: None,
+            discussions: None,
+            file_delivery: None,
         }
     }
 
@@ -905,4 +3059,233 @@ mod tests {
         assert!(cfg.contents.contains("bug.yml"));
         assert!(cfg.contents.contains("feature.yml"));
     }
+
+    #[test]
+    fn builds_config_entries_for_markdown_templates() {
+        let templates = vec![IssueTemplateFile {
+            path: ".github/ISSUE_TEMPLATE/question.md".to_string(),
+            contents: "---\nname: Question\nabout: Ask something\n---\nDetails\n".to_string(),
+        }];
+        let cfg = build_issue_template_config(&templates).expect("config");
+        assert!(cfg.contents.contains("file: question.md"));
+        assert!(cfg.contents.contains("name: Question"));
+        assert!(cfg.contents.contains("description: Ask something"));
+    }
+
+    #[test]
+    fn batches_file_changes_into_one_commit() {
+        let tpl = |path: &str| IssueTemplateFile {
+            path: path.to_string(),
+            contents: "name: x\n".to_string(),
+        };
+        let (changes, message) = file_commit(
+            &[tpl("config-sets/core/.github/ISSUE_TEMPLATE/bug.yml")],
+            &[(tpl(".github/ISSUE_TEMPLATE/config.yml"), "abc".to_string())],
+            &[(
+                ".github/ISSUE_TEMPLATE/old.md".to_string(),
+                "def".to_string(),
+            )],
+        );
+        let paths: Vec<_> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                ".github/ISSUE_TEMPLATE/bug.yml",
+                ".github/ISSUE_TEMPLATE/config.yml",
+                ".github/ISSUE_TEMPLATE/old.md"
+            ]
+        );
+        assert!(changes[2].contents.is_none());
+        assert_eq!(
+            message,
+            "Update .github files via gh-governor\n\n- Add .github/ISSUE_TEMPLATE/bug.yml\n- Update .github/ISSUE_TEMPLATE/config.yml\n- Remove .github/ISSUE_TEMPLATE/old.md"
+        );
+    }
+
+    #[test]
+    fn pr_body_lists_files_with_source_and_diff() {
+        let tpl = |path: &str, contents: &str| IssueTemplateFile {
+            path: path.to_string(),
+            contents: contents.to_string(),
+        };
+        let previous = HashMap::from([(
+            ".github/ISSUE_TEMPLATE/bug.yml".to_string(),
+            "name: Bug\nlabels: [bug]\n".to_string(),
+        )]);
+        let sources = BTreeMap::from([(
+            ".github/ISSUE_TEMPLATE/bug.yml".to_string(),
+            "core".to_string(),
+        )]);
+        let body = pr_body(
+            &[],
+            &[(
+                tpl(
+                    ".github/ISSUE_TEMPLATE/bug.yml",
+                    "name: Bug\nlabels: [bug, triage]\n",
+                ),
+                "sha".to_string(),
+            )],
+            &[(
+                ".github/ISSUE_TEMPLATE/old.yml".to_string(),
+                "sha".to_string(),
+            )],
+            &previous,
+            &sources,
+        );
+        assert!(body.contains("### Update `.github/ISSUE_TEMPLATE/bug.yml`\n\nSource: set `core`"));
+        assert!(body.contains("-labels: [bug]\n+labels: [bug, triage]\n"));
+        assert!(body.contains(
+            "### Remove `.github/ISSUE_TEMPLATE/old.yml`\n\nSource: no longer provided by any set"
+        ));
+    }
+
+    #[test]
+    fn truncates_long_diffs() {
+        let new: String = (0..100).map(|i| format!("line {i}\n")).collect();
+        let diff = unified_diff_lines("a.yml", "", &new, 10);
+        assert_eq!(diff.len(), 11);
+        assert_eq!(diff[10], "... 93 more lines");
+        assert_eq!(unified_diff_lines("a.yml", "", &new, 0).len(), 103);
+    }
+
+    #[test]
+    fn run_summary_lists_failed_operations() {
+        owo_colors::set_override(false);
+        let summary = format_run_summary(&[
+            RunOutcome {
+                name: "api".to_string(),
+                failures: Vec::new(),
+            },
+            RunOutcome {
+                name: "web".to_string(),
+                failures: vec![
+                    ("updating labels", "forbidden".to_string()),
+                    ("updating webhooks", "not found".to_string()),
+                ],
+            },
+            RunOutcome {
+                name: "teams".to_string(),
+                failures: vec![("updating teams", "forbidden".to_string())],
+            },
+        ]);
+        assert_eq!(
+            summary,
+            "Summary: 1 succeeded, 2 failed\n  ok      api\n  FAILED  web (updating labels): forbidden\n  FAILED  web (updating webhooks): not found\n  FAILED  teams (updating teams): forbidden"
+        );
+    }
+
+    /// Answers every request on a local port with `status` and a GitHub-style error body.
+    fn serve_status(status: &'static str) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                // Read the whole request so closing the socket does not reset it.
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                while let Ok(n) = stream.read(&mut buf) {
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_lowercase();
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text
+                            .lines()
+                            .find_map(|l| l.strip_prefix("content-length:"))
+                            .and_then(|v| v.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            break;
+                        }
+                    }
+                }
+                let body = r#"{"message":"Validation Failed"}"#;
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn rejected_mutation_shows_up_in_run_summary() {
+        owo_colors::set_override(false);
+        let gh = GithubClient {
+            inner: octocrab::Octocrab::builder()
+                .base_uri(serve_status("422 Unprocessable Entity"))
+                .unwrap()
+                .build()
+                .unwrap(),
+            org: "acme".to_string(),
+        };
+
+        let mut failures = Vec::new();
+        let result = gh
+            .set_team_permission("web", "devs", crate::settings::Permission::Push)
+            .await;
+        settle("updating access", result, &mut failures, true).unwrap();
+        let summary = format_run_summary(&[RunOutcome {
+            name: "web".to_string(),
+            failures,
+        }]);
+        assert!(
+            summary
+                .starts_with("Summary: 0 succeeded, 1 failed\n  FAILED  web (updating access): "),
+            "{summary}"
+        );
+    }
+
+    #[test]
+    fn report_sections_come_from_diffs() {
+        let skipped = report_section("Protection", None);
+        assert_eq!(skipped.count, None);
+
+        let milestone = |number: u64, title: &str| crate::github::RepoMilestone {
+            number,
+            open_issues: 1,
+            spec: MilestoneSpec {
+                title: title.to_string(),
+                description: None,
+                due_on: None,
+                state: None,
+            },
+        };
+        let diff = MilestoneDiff {
+            to_add: vec![milestone(0, "2027-Q1").spec],
+            blocked: vec![milestone(1, "2026-Q1")],
+            ..Default::default()
+        };
+        let section = report_section("Milestones", Some(milestones_report(&diff)));
+        assert_eq!(section.count, Some(1));
+        assert_eq!(
+            section.details,
+            vec![
+                "- add `2027-Q1`",
+                "- blocked removal of `2026-Q1`, still has 1 open issues"
+            ]
+        );
+    }
+
+    #[test]
+    fn issue_config_ignores_discussion_templates() {
+        let templates = vec![
+            IssueTemplateFile {
+                path: ".github/ISSUE_TEMPLATE/bug.yml".to_string(),
+                contents: "name: Bug\n".to_string(),
+            },
+            IssueTemplateFile {
+                path: ".github/DISCUSSION_TEMPLATE/ideas.yml".to_string(),
+                contents: "title: \"[Idea] \"\n".to_string(),
+            },
+        ];
+        let cfg = build_issue_template_config(&templates).expect("config");
+        assert!(cfg.contents.contains("bug.yml"));
+        assert!(!cfg.contents.contains("ideas.yml"));
+    }
 }
@@ -4,6 +4,7 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, Result};
+use crate::settings::{CustomProperties, FileDelivery, OrgSettings, TeamsConfig};
 use crate::util::{SUPPORTED_EXTS, parse_by_extension};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -11,6 +12,102 @@ pub struct RepoConfig {
     pub name: String,
     #[serde(default)]
     pub sets: Vec<String>,
+    /// Custom property values for this repository; these take precedence over values from sets.
+    #[serde(default, skip_serializing_if = "CustomProperties::is_empty")]
+    pub properties: CustomProperties,
+    /// Overrides for the governance PR opened in this repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_request: Option<PullRequestConfig>,
+    /// Overrides the `file_delivery` chosen by this repository's sets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_delivery: Option<FileDelivery>,
+}
+
+/// How gh-governor opens the PR carrying `.github` file changes.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct PullRequestConfig {
+    /// Open the PR as a draft; defaults to true.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draft: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reviewers: Vec<String>,
+    /// Team slugs requested for review.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub team_reviewers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assignees: Vec<String>,
+    /// Request auto-merge with this method once required checks pass. Ignored for drafts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_merge: Option<AutoMergeMethod>,
+    /// What to do when the open PR is behind or conflicts with its base; defaults to
+    /// `recreate`. Branches holding commits not made by gh-governor are left as they are.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_branch: Option<StaleBranchAction>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StaleBranchAction {
+    /// Rebuild the branch as one commit on the latest base, keeping the PR.
+    #[default]
+    Recreate,
+    /// Close the outdated PR, delete its branch and open a fresh one from the latest base.
+    Reopen,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoMergeMethod {
+    Merge,
+    Squash,
+    Rebase,
+}
+
+impl AutoMergeMethod {
+    /// Value of the GraphQL `PullRequestMergeMethod` enum.
+    pub fn graphql_name(self) -> &'static str {
+        match self {
+            AutoMergeMethod::Merge => "MERGE",
+            AutoMergeMethod::Squash => "SQUASH",
+            AutoMergeMethod::Rebase => "REBASE",
+        }
+    }
+}
+
+impl PullRequestConfig {
+    /// Applies a repository override: set options and non-empty lists replace the root values.
+    pub fn overlay(&self, repo: &PullRequestConfig) -> PullRequestConfig {
+        let pick = |base: &Vec<String>, over: &Vec<String>| {
+            if over.is_empty() {
+                base.clone()
+            } else {
+                over.clone()
+            }
+        };
+        PullRequestConfig {
+            draft: repo.draft.or(self.draft),
+            labels: pick(&self.labels, &repo.labels),
+            reviewers: pick(&self.reviewers, &repo.reviewers),
+            team_reviewers: pick(&self.team_reviewers, &repo.team_reviewers),
+            assignees: pick(&self.assignees, &repo.assignees),
+            auto_merge: repo.auto_merge.or(self.auto_merge),
+            stale_branch: repo.stale_branch.or(self.stale_branch),
+        }
+    }
+
+    pub fn is_draft(&self) -> bool {
+        self.draft.unwrap_or(true)
+    }
+}
+
+/// Assigns sets to every repository whose custom property values match all `properties`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RepoSelector {
+    pub properties: CustomProperties,
+    #[serde(default)]
+    pub sets: Vec<String>,
 }
 
 /// Root configuration read from `gh-governor-conf.{toml,yml,yaml,json}`.
@@ -27,6 +124,53 @@ pub struct RootConfig {
     /// Optional directory for configuration sets (relative to base); defaults to `config-sets/`.
     #[serde(default)]
     pub config_sets_dir: Option<String>,
+    /// Select repositories by their custom property values instead of listing them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repo_selectors: Vec<RepoSelector>,
+    /// Options for governance PRs; repositories may override them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_request: Option<PullRequestConfig>,
+}
+
+impl RootConfig {
+    /// Expands `repo_selectors` into `repos` using the organization's current custom
+    /// property values. Selected sets go after `default_sets` and before a repo's own sets.
+    pub fn apply_repo_selectors(&mut self, org_values: &[(String, CustomProperties)]) {
+        for (repo_name, values) in org_values {
+            let mut selected: Vec<String> = Vec::new();
+            for selector in &self.repo_selectors {
+                let matched = selector.properties.iter().all(|(name, wanted)| {
+                    values
+                        .get(name)
+                        .is_some_and(|actual| wanted.matches(actual))
+                });
+                if matched {
+                    for set in &selector.sets {
+                        if !selected.contains(set) {
+                            selected.push(set.clone());
+                        }
+                    }
+                }
+            }
+            if selected.is_empty() {
+                continue;
+            }
+            match self.repos.iter_mut().find(|r| &r.name == repo_name) {
+                Some(repo) => {
+                    selected.retain(|s| !repo.sets.contains(s));
+                    selected.append(&mut repo.sets);
+                    repo.sets = selected;
+                }
+                None => self.repos.push(RepoConfig {
+                    name: repo_name.clone(),
+                    sets: selected,
+                    properties: CustomProperties::new(),
+                    pull_request: None,
+                    file_delivery: None,
+                }),
+            }
+        }
+    }
 }
 
 const MAIN_CONFIG_BASENAME: &str = "gh-governor-conf";
@@ -38,6 +182,16 @@ pub fn load_root_config(base: &Path) -> Result<(RootConfig, PathBuf)> {
     Ok((cfg, path))
 }
 
+/// Loads the optional `org-settings.{toml,yml,yaml,json}` located next to the root config.
+pub fn load_org_settings(base: &Path) -> Result<Option<OrgSettings>> {
+    crate::sets::load_named_file(base, "org-settings")
+}
+
+/// Loads the optional `teams.{toml,yml,yaml,json}` located next to the root config.
+pub fn load_teams(base: &Path) -> Result<Option<TeamsConfig>> {
+    crate::sets::load_named_file(base, "teams")
+}
+
 pub fn resolve_sets_dir(base: &Path, root: &RootConfig) -> PathBuf {
     match &root.config_sets_dir {
         Some(dir) => base.join(dir),
@@ -56,3 +210,73 @@ fn find_main_config(base: &Path) -> Result<PathBuf> {
         base: base.to_path_buf(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::PropertyValue;
+
+    fn props(pairs: &[(&str, &str)]) -> CustomProperties {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), PropertyValue::Single(v.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn selectors_add_sets_to_matching_repos() {
+        let mut root = RootConfig {
+            org: "acme".to_string(),
+            default_sets: vec!["core".to_string()],
+            repos: vec![RepoConfig {
+                name: "api".to_string(),
+                sets: vec!["rust".to_string()],
+                properties: CustomProperties::new(),
+                pull_request: None,
+                file_delivery: None,
+            }],
+            config_sets_dir: None,
+            repo_selectors: vec![RepoSelector {
+                properties: props(&[("tier", "critical")]),
+                sets: vec!["strict".to_string(), "rust".to_string()],
+            }],
+            pull_request: None,
+        };
+        let org_values = vec![
+            ("api".to_string(), props(&[("tier", "critical")])),
+            (
+                "web".to_string(),
+                props(&[("tier", "critical"), ("team", "ui")]),
+            ),
+            ("docs".to_string(), props(&[("tier", "low")])),
+        ];
+
+        root.apply_repo_selectors(&org_values);
+
+        let names: Vec<_> = root.repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "web"]);
+        assert_eq!(root.repos[0].sets, vec!["strict", "rust"]);
+        assert_eq!(root.repos[1].sets, vec!["strict", "rust"]);
+    }
+
+    #[test]
+    fn repo_pull_request_options_override_root() {
+        let root = PullRequestConfig {
+            labels: vec!["governance".to_string()],
+            reviewers: vec!["octocat".to_string()],
+            auto_merge: Some(AutoMergeMethod::Squash),
+            ..Default::default()
+        };
+        let repo = PullRequestConfig {
+            draft: Some(false),
+            reviewers: vec!["hubot".to_string()],
+            ..Default::default()
+        };
+        let effective = root.overlay(&repo);
+        assert!(!effective.is_draft());
+        assert_eq!(effective.labels, vec!["governance"]);
+        assert_eq!(effective.reviewers, vec!["hubot"]);
+        assert_eq!(effective.auto_merge, Some(AutoMergeMethod::Squash));
+        assert!(PullRequestConfig::default().is_draft());
+    }
+}
@@ -1,7 +1,18 @@
+use std::collections::BTreeMap;
+
 use octocrab::models::Label;
+use serde::Serialize;
 
-use crate::sets::LabelSpec;
-use crate::settings::RepoSettings;
+use crate::github::{
+    OrgTeam, RepoAutolink, RepoDiscussionCategory, RepoDiscussions, RepoMilestone, RepoWebhook,
+};
+use crate::sets::{LabelSpec, MilestoneSpec, MilestonesConfig};
+use crate::settings::{
+    AccessConfig, ActionsSettings, AutolinkSpec, AutolinksConfig, CustomProperties,
+    DiscussionCategorySpec, DiscussionFormat, DiscussionsConfig, EnvironmentSpec,
+    EnvironmentsConfig, ManageMode, OrgSettings, Permission, PropertyValue, PullRequestSettings,
+    RepoSettings, SecuritySettings, TeamRole, TeamSpec, TeamsConfig, WebhookSpec, WebhooksConfig,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LabelDiff {
@@ -50,45 +61,748 @@ pub fn diff_labels(desired: &[LabelSpec], current: &[Label]) -> LabelDiff {
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct MilestoneDiff {
+    pub to_add: Vec<MilestoneSpec>,
+    pub to_update: Vec<(u64, MilestoneSpec)>,
+    pub to_remove: Vec<RepoMilestone>,
+    /// Undeclared milestones that still have issues, open or closed; these are never deleted.
+    pub blocked: Vec<RepoMilestone>,
+}
+
+/// Undeclared milestones are only considered for removal in `exclusive` mode.
+pub fn diff_milestones(desired: &MilestonesConfig, current: &[RepoMilestone]) -> MilestoneDiff {
+    let mut diff = MilestoneDiff::default();
+
+    for want in &desired.milestones {
+        match current.iter().find(|c| c.spec.title == want.title) {
+            None => diff.to_add.push(want.clone()),
+            Some(existing) => {
+                let have = &existing.spec;
+                let changed = (want.description.is_some() && want.description != have.description)
+                    || (want.due_on.is_some() && want.due_on != have.due_on)
+                    || (want.state.is_some() && want.state != have.state);
+                if changed {
+                    diff.to_update.push((existing.number, want.clone()));
+                }
+            }
+        }
+    }
+
+    if desired.mode == Some(ManageMode::Exclusive) {
+        for existing in current {
+            if !desired
+                .milestones
+                .iter()
+                .any(|d| d.title == existing.spec.title)
+            {
+                if existing.open_issues > 0 {
+                    diff.blocked.push(existing.clone());
+                } else {
+                    diff.to_remove.push(existing.clone());
+                }
+            }
+        }
+    }
+
+    diff.to_add.sort_by(|a, b| a.title.cmp(&b.title));
+    diff.to_update.sort_by(|a, b| a.1.title.cmp(&b.1.title));
+    diff.to_remove
+        .sort_by(|a, b| a.spec.title.cmp(&b.spec.title));
+    diff.blocked.sort_by(|a, b| a.spec.title.cmp(&b.spec.title));
+    diff
+}
+
 fn normalize_color(color: &Option<String>) -> Option<String> {
     color
         .as_ref()
         .map(|c| c.trim_start_matches('#').to_lowercase())
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    Team,
+    Collaborator,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessChange {
+    pub kind: AccessKind,
+    pub name: String,
+    pub current: Option<Permission>,
+    pub desired: Option<Permission>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AccessDiff {
+    pub to_add: Vec<AccessChange>,
+    pub to_update: Vec<AccessChange>,
+    pub to_remove: Vec<AccessChange>,
+}
+
+impl AccessDiff {
+    pub fn is_empty(&self) -> bool {
+        self.to_add.is_empty() && self.to_update.is_empty() && self.to_remove.is_empty()
+    }
+}
+
+/// Compares desired access with the repository's current teams and outside collaborators.
+/// Entries missing from the desired config are only removed in exclusive mode.
+pub fn diff_access(desired: &AccessConfig, current: &AccessConfig) -> AccessDiff {
+    let mut diff = AccessDiff::default();
+    let exclusive = desired.mode == Some(ManageMode::Exclusive);
+
+    for (kind, want, have) in [
+        (AccessKind::Team, &desired.teams, &current.teams),
+        (
+            AccessKind::Collaborator,
+            &desired.collaborators,
+            &current.collaborators,
+        ),
+    ] {
+        let find = |map: &BTreeMap<String, Permission>, name: &str| {
+            map.iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| *v)
+        };
+        for (name, perm) in want {
+            match find(have, name) {
+                None => diff.to_add.push(AccessChange {
+                    kind,
+                    name: name.clone(),
+                    current: None,
+                    desired: Some(*perm),
+                }),
+                Some(existing) if existing != *perm => diff.to_update.push(AccessChange {
+                    kind,
+                    name: name.clone(),
+                    current: Some(existing),
+                    desired: Some(*perm),
+                }),
+                _ => {}
+            }
+        }
+        if exclusive {
+            for (name, perm) in have {
+                if find(want, name).is_none() {
+                    diff.to_remove.push(AccessChange {
+                        kind,
+                        name: name.clone(),
+                        current: Some(*perm),
+                        desired: None,
+                    });
+                }
+            }
+        }
+    }
+
+    diff
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookUpdate {
+    pub id: u64,
+    pub spec: WebhookSpec,
+    /// Whether `content_type` or `insecure_ssl` changed, which requires resending the
+    /// hook's whole `config`, secret included.
+    pub config_changed: bool,
+    pub has_secret: bool,
+}
+
+impl WebhookUpdate {
+    /// The current secret would be lost: the config must be resent but no `secret_env`
+    /// provides the secret again.
+    pub fn loses_secret(&self) -> bool {
+        self.config_changed && self.has_secret && self.spec.secret_env.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WebhookDiff {
+    pub to_add: Vec<WebhookSpec>,
+    pub to_update: Vec<WebhookUpdate>,
+    /// (hook id, url)
+    pub to_remove: Vec<(u64, String)>,
+}
+
+impl WebhookDiff {
+    pub fn is_empty(&self) -> bool {
+        self.to_add.is_empty() && self.to_update.is_empty() && self.to_remove.is_empty()
+    }
+}
+
+/// Matches hooks by URL; only fields set in the desired spec are compared. Hooks with
+/// unmanaged URLs are removed only in exclusive mode.
+pub fn diff_webhooks(desired: &WebhooksConfig, current: &[RepoWebhook]) -> WebhookDiff {
+    let mut diff = WebhookDiff::default();
+
+    for want in &desired.hooks {
+        match current.iter().find(|c| c.spec.url == want.url) {
+            None => diff.to_add.push(want.clone()),
+            Some(existing) => {
+                let have = &existing.spec;
+                let sorted = |v: &Vec<String>| {
+                    let mut v = v.clone();
+                    v.sort();
+                    v
+                };
+                let config_changed = (want.content_type.is_some()
+                    && want.content_type != have.content_type)
+                    || (want.insecure_ssl.is_some() && want.insecure_ssl != have.insecure_ssl);
+                let differs = config_changed
+                    || (want.active.is_some() && want.active != have.active)
+                    || (want.events.is_some()
+                        && want.events.as_ref().map(sorted) != have.events.as_ref().map(sorted));
+                if differs {
+                    diff.to_update.push(WebhookUpdate {
+                        id: existing.id,
+                        spec: want.clone(),
+                        config_changed,
+                        has_secret: existing.has_secret,
+                    });
+                }
+            }
+        }
+    }
+
+    if desired.mode == Some(ManageMode::Exclusive) {
+        for existing in current {
+            if !desired.hooks.iter().any(|d| d.url == existing.spec.url) {
+                diff.to_remove
+                    .push((existing.id, existing.spec.url.clone()));
+            }
+        }
+    }
+
+    diff.to_add.sort_by(|a, b| a.url.cmp(&b.url));
+    diff.to_update.sort_by(|a, b| a.spec.url.cmp(&b.spec.url));
+    diff.to_remove.sort_by(|a, b| a.1.cmp(&b.1));
+    diff
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyChange {
+    pub name: String,
+    pub current: Option<PropertyValue>,
+    pub desired: PropertyValue,
+}
+
+/// Properties not declared in config are left untouched. Multi-select values are
+/// compared regardless of order.
+pub fn diff_properties(
+    desired: &CustomProperties,
+    current: &CustomProperties,
+) -> Vec<PropertyChange> {
+    fn normalized(value: &PropertyValue) -> Vec<&str> {
+        let mut values = value.values();
+        values.sort_unstable();
+        values
+    }
+    desired
+        .iter()
+        .filter(|(name, want)| {
+            current
+                .get(*name)
+                .is_none_or(|have| normalized(have) != normalized(want))
+        })
+        .map(|(name, want)| PropertyChange {
+            name: name.clone(),
+            current: current.get(name).cloned(),
+            desired: want.clone(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AutolinkDiff {
+    pub to_add: Vec<AutolinkSpec>,
+    /// Autolinks cannot be edited, so a changed autolink is deleted (by id) and recreated.
+    pub to_replace: Vec<(u64, AutolinkSpec)>,
+    pub to_remove: Vec<(u64, AutolinkSpec)>,
+}
+
+impl AutolinkDiff {
+    pub fn is_empty(&self) -> bool {
+        self.to_add.is_empty() && self.to_replace.is_empty() && self.to_remove.is_empty()
+    }
+}
+
+pub fn diff_autolinks(desired: &AutolinksConfig, current: &[RepoAutolink]) -> AutolinkDiff {
+    let mut diff = AutolinkDiff::default();
+    for want in &desired.autolinks {
+        match current
+            .iter()
+            .find(|c| c.spec.key_prefix == want.key_prefix)
+        {
+            None => diff.to_add.push(want.clone()),
+            Some(existing) => {
+                let have = &existing.spec;
+                if have.url_template != want.url_template
+                    || have.is_alphanumeric.unwrap_or(true) != want.is_alphanumeric.unwrap_or(true)
+                {
+                    diff.to_replace.push((existing.id, want.clone()));
+                }
+            }
+        }
+    }
+    if desired.mode == Some(ManageMode::Exclusive) {
+        for existing in current {
+            if !desired
+                .autolinks
+                .iter()
+                .any(|d| d.key_prefix == existing.spec.key_prefix)
+            {
+                diff.to_remove.push((existing.id, existing.spec.clone()));
+            }
+        }
+    }
+    diff.to_add.sort_by(|a, b| a.key_prefix.cmp(&b.key_prefix));
+    diff.to_replace
+        .sort_by(|a, b| a.1.key_prefix.cmp(&b.1.key_prefix));
+    diff.to_remove
+        .sort_by(|a, b| a.1.key_prefix.cmp(&b.1.key_prefix));
+    diff
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvironmentChange {
+    pub spec: EnvironmentSpec,
+    pub exists: bool,
+    /// Fields that differ from the current environment (empty on creation).
+    pub fields: Vec<&'static str>,
+}
+
+/// Compares declared environments with existing ones by name; only fields set in the desired
+/// spec are compared.
+pub fn diff_environments(
+    desired: &EnvironmentsConfig,
+    current: &[EnvironmentSpec],
+) -> Vec<EnvironmentChange> {
+    let mut changes = Vec::new();
+    for want in &desired.environments {
+        let Some(have) = current.iter().find(|c| c.name == want.name) else {
+            changes.push(EnvironmentChange {
+                spec: want.clone(),
+                exists: false,
+                fields: Vec::new(),
+            });
+            continue;
+        };
+        let sorted = |v: &Vec<String>| {
+            let mut v = v.clone();
+            v.sort();
+            v
+        };
+        let mut fields = Vec::new();
+        if want.wait_timer.is_some() && want.wait_timer != have.wait_timer.or(Some(0)) {
+            fields.push("wait_timer");
+        }
+        if let Some(reviewers) = &want.reviewers {
+            let current = have.reviewers.clone().unwrap_or_default();
+            if sorted(&reviewers.users) != sorted(&current.users)
+                || sorted(&reviewers.teams) != sorted(&current.teams)
+            {
+                fields.push("reviewers");
+            }
+        }
+        if want.prevent_self_review.is_some()
+            && want.prevent_self_review != have.prevent_self_review.or(Some(false))
+        {
+            fields.push("prevent_self_review");
+        }
+        if want.can_admins_bypass.is_some() && want.can_admins_bypass != have.can_admins_bypass {
+            fields.push("can_admins_bypass");
+        }
+        if let Some(policy) = &want.deployment_branch_policy {
+            // GitHub reports "any branch" as no policy at all.
+            let current = have.deployment_branch_policy.clone().unwrap_or_default();
+            if policy.protected_branches != current.protected_branches
+                || sorted(&policy.custom_branches) != sorted(&current.custom_branches)
+            {
+                fields.push("deployment_branch_policy");
+            }
+        }
+        if !fields.is_empty() {
+            changes.push(EnvironmentChange {
+                spec: want.clone(),
+                exists: true,
+                fields,
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.spec.name.cmp(&b.spec.name));
+    changes
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RepoSettingsDiff {
     pub changes: Vec<SettingChange>,
+    /// Desired settings GitHub does not offer for this repository (plan or permissions).
+    pub unavailable: Vec<&'static str>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SettingChange {
+    pub category: SettingCategory,
     pub field: &'static str,
     pub current: Option<String>,
     pub desired: String,
 }
 
-pub fn diff_repo_settings(desired: &RepoSettings, current: &RepoSettings) -> RepoSettingsDiff {
-    let mut changes = Vec::new();
+/// The API a setting is changed through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingCategory {
+    /// Merge options on the repository itself.
+    PullRequests,
+    Actions,
+    Security,
+    Organization,
+}
 
-    let desired_pr = match &desired.pull_requests {
-        Some(pr) => pr,
-        None => {
-            return RepoSettingsDiff { changes };
-        }
+#[derive(Debug, Clone, Default)]
+pub struct DiscussionsDiff {
+    /// Desired value of the repository's discussions toggle, when it differs.
+    pub enable: Option<bool>,
+    pub to_add: Vec<DiscussionCategorySpec>,
+    pub to_update: Vec<(DiscussionCategorySpec, Vec<&'static str>)>,
+    pub to_remove: Vec<RepoDiscussionCategory>,
+    /// Undeclared categories that still contain discussions; these are never removed.
+    pub blocked: Vec<RepoDiscussionCategory>,
+}
+
+impl DiscussionsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.enable.is_none()
+            && self.to_add.is_empty()
+            && self.to_update.is_empty()
+            && self.to_remove.is_empty()
+            && self.blocked.is_empty()
+    }
+
+    /// Whether category changes are pending; GitHub offers no API to make them.
+    pub fn has_category_changes(&self) -> bool {
+        !self.to_add.is_empty() || !self.to_update.is_empty() || !self.to_remove.is_empty()
+    }
+}
+
+pub fn diff_discussions(desired: &DiscussionsConfig, current: &RepoDiscussions) -> DiscussionsDiff {
+    let mut diff = DiscussionsDiff {
+        enable: desired.enabled.filter(|want| *want != current.enabled),
+        ..Default::default()
     };
-    let current_pr = current.pull_requests.as_ref();
 
-    let mut check = |field: &'static str, want: Option<bool>, have: Option<bool>| {
-        if let Some(target) = want {
-            if have != Some(target) {
-                changes.push(SettingChange {
-                    field,
-                    current: have.map(|v| v.to_string()),
-                    desired: target.to_string(),
+    for want in &desired.categories {
+        match current
+            .categories
+            .iter()
+            .find(|c| c.spec.name.eq_ignore_ascii_case(&want.name))
+        {
+            None => diff.to_add.push(want.clone()),
+            Some(existing) => {
+                let have = &existing.spec;
+                let mut fields = Vec::new();
+                if want.emoji.is_some() && want.emoji != have.emoji {
+                    fields.push("emoji");
+                }
+                if want.description.is_some() && want.description != have.description {
+                    fields.push("description");
+                }
+                // Only answerability can be read back, so only question vs. other is compared.
+                if let Some(format) = want.format
+                    && (format == DiscussionFormat::Question)
+                        != (have.format == Some(DiscussionFormat::Question))
+                {
+                    fields.push("format");
+                }
+                if !fields.is_empty() {
+                    diff.to_update.push((want.clone(), fields));
+                }
+            }
+        }
+    }
+
+    if desired.mode == Some(ManageMode::Exclusive) {
+        for existing in &current.categories {
+            if desired
+                .categories
+                .iter()
+                .any(|d| d.name.eq_ignore_ascii_case(&existing.spec.name))
+            {
+                continue;
+            }
+            if existing.discussions > 0 {
+                diff.blocked.push(existing.clone());
+            } else {
+                diff.to_remove.push(existing.clone());
+            }
+        }
+    }
+    diff
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamUpdate {
+    /// Slug of the existing team.
+    pub slug: String,
+    pub spec: TeamSpec,
+    pub fields: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MembershipChange {
+    /// Desired team name.
+    pub team: String,
+    pub user: String,
+    pub current: Option<TeamRole>,
+    /// `None` removes the user from the team.
+    pub desired: Option<TeamRole>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TeamsDiff {
+    /// Parents come before their children.
+    pub to_create: Vec<TeamSpec>,
+    pub to_update: Vec<TeamUpdate>,
+    pub memberships: Vec<MembershipChange>,
+    /// Slugs of existing teams not declared in config; only deleted when pruning.
+    pub unmanaged: Vec<String>,
+}
+
+impl TeamsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.to_create.is_empty()
+            && self.to_update.is_empty()
+            && self.memberships.is_empty()
+            && self.unmanaged.is_empty()
+    }
+}
+
+fn team_matches(team: &OrgTeam, name: &str) -> bool {
+    team.spec.name.eq_ignore_ascii_case(name) || team.slug.eq_ignore_ascii_case(name)
+}
+
+fn team_roles(spec: &TeamSpec) -> BTreeMap<String, (String, TeamRole)> {
+    let mut roles = BTreeMap::new();
+    for user in &spec.members {
+        roles.insert(user.to_lowercase(), (user.clone(), TeamRole::Member));
+    }
+    for user in &spec.maintainers {
+        roles.insert(user.to_lowercase(), (user.clone(), TeamRole::Maintainer));
+    }
+    roles
+}
+
+pub fn diff_teams(desired: &TeamsConfig, current: &[OrgTeam]) -> TeamsDiff {
+    let mut diff = TeamsDiff::default();
+    let mut matched: Vec<&str> = Vec::new();
+
+    for spec in &desired.teams {
+        let existing = current
+            .iter()
+            .find(|t| team_matches(t, &spec.name))
+            .or_else(|| {
+                spec.renamed_from
+                    .as_deref()
+                    .and_then(|old| current.iter().find(|t| team_matches(t, old)))
+            });
+
+        let have_roles = match existing {
+            None => {
+                diff.to_create.push(spec.clone());
+                BTreeMap::new()
+            }
+            Some(team) => {
+                matched.push(&team.slug);
+                let mut fields = Vec::new();
+                if team.spec.name != spec.name {
+                    fields.push("name");
+                }
+                if spec.description.is_some() && team.spec.description != spec.description {
+                    fields.push("description");
+                }
+                if spec.privacy.is_some() && team.spec.privacy != spec.privacy {
+                    fields.push("privacy");
+                }
+                let same_parent = match (&spec.parent, team.spec.parent.as_deref()) {
+                    (None, None) => true,
+                    (Some(parent), Some(have)) => {
+                        have.eq_ignore_ascii_case(parent)
+                            || current
+                                .iter()
+                                .any(|t| team_matches(t, parent) && t.spec.name == have)
+                    }
+                    _ => false,
+                };
+                if !same_parent {
+                    fields.push("parent");
+                }
+                if !fields.is_empty() {
+                    diff.to_update.push(TeamUpdate {
+                        slug: team.slug.clone(),
+                        spec: spec.clone(),
+                        fields,
+                    });
+                }
+                team_roles(&team.spec)
+            }
+        };
+
+        let want_roles = team_roles(spec);
+        for (key, (user, role)) in &want_roles {
+            let current_role = have_roles.get(key).map(|(_, r)| *r);
+            if current_role != Some(*role) {
+                diff.memberships.push(MembershipChange {
+                    team: spec.name.clone(),
+                    user: user.clone(),
+                    current: current_role,
+                    desired: Some(*role),
+                });
+            }
+        }
+        for (key, (user, role)) in &have_roles {
+            if !want_roles.contains_key(key) {
+                diff.memberships.push(MembershipChange {
+                    team: spec.name.clone(),
+                    user: user.clone(),
+                    current: Some(*role),
+                    desired: None,
                 });
             }
         }
+    }
+
+    // Deleting a parent team also deletes its children, so only the topmost unmanaged
+    // team of each subtree is listed.
+    let unmanaged: Vec<&OrgTeam> = current
+        .iter()
+        .filter(|t| !matched.contains(&t.slug.as_str()))
+        .collect();
+    diff.unmanaged = unmanaged
+        .iter()
+        .filter(|t| {
+            !t.spec
+                .parent
+                .as_deref()
+                .is_some_and(|p| unmanaged.iter().any(|u| u.spec.name == p))
+        })
+        .map(|t| t.slug.clone())
+        .collect();
+    diff.unmanaged.sort();
+
+    let depth = |spec: &TeamSpec| {
+        let mut depth = 0;
+        let mut parent = spec.parent.as_deref();
+        while let Some(name) = parent
+            && depth < desired.teams.len()
+        {
+            depth += 1;
+            parent = desired
+                .teams
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(name))
+                .and_then(|t| t.parent.as_deref());
+        }
+        depth
+    };
+    diff.to_create.sort_by_key(|spec| depth(spec));
+    diff
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OrgSettingsDiff {
+    pub changes: Vec<SettingChange>,
+    /// Set when two-factor authentication is required by config but not enforced; this
+    /// cannot be changed through the API.
+    pub two_factor: Option<SettingChange>,
+}
+
+pub fn diff_org_settings(desired: &OrgSettings, current: &OrgSettings) -> OrgSettingsDiff {
+    let mut changes = Vec::new();
+    push_if_changed(
+        &mut changes,
+        SettingCategory::Organization,
+        "default_repository_permission",
+        desired.default_repository_permission.as_ref(),
+        current.default_repository_permission.as_ref(),
+    );
+    push_if_changed(
+        &mut changes,
+        SettingCategory::Organization,
+        "members_can_create_repositories",
+        desired.members_can_create_repositories.as_ref(),
+        current.members_can_create_repositories.as_ref(),
+    );
+    push_if_changed(
+        &mut changes,
+        SettingCategory::Organization,
+        "members_can_create_pages",
+        desired.members_can_create_pages.as_ref(),
+        current.members_can_create_pages.as_ref(),
+    );
+    push_if_changed(
+        &mut changes,
+        SettingCategory::Organization,
+        "members_can_fork_private_repositories",
+        desired.members_can_fork_private_repositories.as_ref(),
+        current.members_can_fork_private_repositories.as_ref(),
+    );
+
+    let mut two_factor = Vec::new();
+    push_if_changed(
+        &mut two_factor,
+        SettingCategory::Organization,
+        "two_factor_requirement_enabled",
+        desired.two_factor_requirement_enabled.as_ref(),
+        current.two_factor_requirement_enabled.as_ref(),
+    );
+
+    OrgSettingsDiff {
+        changes,
+        two_factor: two_factor.pop(),
+    }
+}
+
+pub fn diff_repo_settings(desired: &RepoSettings, current: &RepoSettings) -> RepoSettingsDiff {
+    let mut changes = Vec::new();
+
+    if let Some(desired_pr) = &desired.pull_requests {
+        diff_pull_request_settings(desired_pr, current.pull_requests.as_ref(), &mut changes);
+    }
+    if let Some(desired_actions) = &desired.actions {
+        diff_actions_settings(desired_actions, current.actions.as_ref(), &mut changes);
+    }
+    let mut unavailable = Vec::new();
+    if let Some(desired_security) = &desired.security {
+        diff_security_settings(
+            desired_security,
+            current.security.as_ref(),
+            &mut changes,
+            &mut unavailable,
+        );
+    }
+
+    RepoSettingsDiff {
+        changes,
+        unavailable,
+    }
+}
+
+fn diff_pull_request_settings(
+    desired_pr: &PullRequestSettings,
+    current_pr: Option<&PullRequestSettings>,
+    changes: &mut Vec<SettingChange>,
+) {
+    let mut check = |field: &'static str, want: Option<bool>, have: Option<bool>| {
+        if let Some(target) = want
+            && have != Some(target)
+        {
+            changes.push(SettingChange {
+                category: SettingCategory::PullRequests,
+                field,
+                current: have.map(|v| v.to_string()),
+                desired: target.to_string(),
+            });
+        }
     };
 
     check(
@@ -117,58 +831,214 @@ pub fn diff_repo_settings(desired: &RepoSettings, current: &RepoSettings) -> Rep
         current_pr.and_then(|p| p.delete_branch_on_merge),
     );
 
-    if desired_pr.merge_commit_message_option.is_some() {
-        let (title, msg) = crate::settings::map_merge_message_option(
-            desired_pr
-                .merge_commit_message_option
-                .as_ref()
-                .expect("checked is_some"),
-        );
-        let title_str = title.as_ref().map(|v| format!("{:?}", v));
-        let msg_str = msg.as_ref().map(|v| format!("{:?}", v));
-        // Cannot read current merge title/message; treat as desired change whenever set.
-        if title.is_some() || msg.is_some() {
+    if let Some(want) = &desired_pr.merge_commit_message_option {
+        let have = current_pr.and_then(|p| p.merge_commit_message_option.as_ref());
+        if have != Some(want) {
             changes.push(SettingChange {
+                category: SettingCategory::PullRequests,
                 field: "merge_commit_message_option",
-                current: None,
-                desired: format!(
-                    "{} / {}",
-                    title_str.unwrap_or_else(|| "unset".to_string()),
-                    msg_str.unwrap_or_else(|| "unset".to_string())
-                ),
+                current: have.map(|h| {
+                    let (title, msg) = crate::settings::map_merge_message_option(h);
+                    format_title_message(title.as_ref(), msg.as_ref())
+                }),
+                desired: {
+                    let (title, msg) = crate::settings::map_merge_message_option(want);
+                    format_title_message(title.as_ref(), msg.as_ref())
+                },
             });
         }
     }
 
-    if desired_pr.squash_merge_option.is_some() {
-        let (title, msg) = crate::settings::map_squash_option(
-            desired_pr
-                .squash_merge_option
-                .as_ref()
-                .expect("checked is_some"),
-        );
-        let title_str = title.as_ref().map(|v| format!("{:?}", v));
-        let msg_str = msg.as_ref().map(|v| format!("{:?}", v));
-        // We cannot read current squash title/message via Octocrab, so always treat option as a desired change.
-        if title.is_some() || msg.is_some() {
+    if let Some(want) = &desired_pr.squash_merge_option {
+        let have = current_pr.and_then(|p| p.squash_merge_option.as_ref());
+        if have != Some(want) {
             changes.push(SettingChange {
+                category: SettingCategory::PullRequests,
                 field: "squash_merge_option",
-                current: None,
-                desired: format!(
-                    "{} / {}",
-                    title_str.unwrap_or_else(|| "unset".to_string()),
-                    msg_str.unwrap_or_else(|| "unset".to_string())
-                ),
+                current: have.map(|h| {
+                    let (title, msg) = crate::settings::map_squash_option(h);
+                    format_title_message(title.as_ref(), msg.as_ref())
+                }),
+                desired: {
+                    let (title, msg) = crate::settings::map_squash_option(want);
+                    format_title_message(title.as_ref(), msg.as_ref())
+                },
             });
         }
     }
+}
+
+fn diff_actions_settings(
+    desired: &ActionsSettings,
+    current: Option<&ActionsSettings>,
+    changes: &mut Vec<SettingChange>,
+) {
+    push_if_changed(
+        changes,
+        SettingCategory::Actions,
+        "actions.enabled",
+        desired.enabled.as_ref(),
+        current.and_then(|c| c.enabled.as_ref()),
+    );
+    push_if_changed(
+        changes,
+        SettingCategory::Actions,
+        "actions.allowed_actions",
+        desired.allowed_actions.as_ref(),
+        current.and_then(|c| c.allowed_actions.as_ref()),
+    );
+    if let Some(want) = &desired.selected_actions {
+        let have = current.and_then(|c| c.selected_actions.as_ref());
+        push_if_changed(
+            changes,
+            SettingCategory::Actions,
+            "actions.selected_actions.github_owned_allowed",
+            want.github_owned_allowed.as_ref(),
+            have.and_then(|h| h.github_owned_allowed.as_ref()),
+        );
+        push_if_changed(
+            changes,
+            SettingCategory::Actions,
+            "actions.selected_actions.verified_allowed",
+            want.verified_allowed.as_ref(),
+            have.and_then(|h| h.verified_allowed.as_ref()),
+        );
+        let sorted = |v: &Vec<String>| {
+            let mut v = v.clone();
+            v.sort();
+            v
+        };
+        push_if_changed(
+            changes,
+            SettingCategory::Actions,
+            "actions.selected_actions.patterns_allowed",
+            want.patterns_allowed.as_ref().map(sorted).as_ref(),
+            have.and_then(|h| h.patterns_allowed.as_ref())
+                .map(sorted)
+                .as_ref(),
+        );
+    }
+    push_if_changed(
+        changes,
+        SettingCategory::Actions,
+        "actions.default_workflow_permissions",
+        desired.default_workflow_permissions.as_ref(),
+        current.and_then(|c| c.default_workflow_permissions.as_ref()),
+    );
+    push_if_changed(
+        changes,
+        SettingCategory::Actions,
+        "actions.can_approve_pull_request_reviews",
+        desired.can_approve_pull_request_reviews.as_ref(),
+        current.and_then(|c| c.can_approve_pull_request_reviews.as_ref()),
+    );
+    push_if_changed(
+        changes,
+        SettingCategory::Actions,
+        "actions.fork_pr_approval",
+        desired.fork_pr_approval.as_ref(),
+        current.and_then(|c| c.fork_pr_approval.as_ref()),
+    );
+}
+
+fn diff_security_settings(
+    desired: &SecuritySettings,
+    current: Option<&SecuritySettings>,
+    changes: &mut Vec<SettingChange>,
+    unavailable: &mut Vec<&'static str>,
+) {
+    let fields: [(&'static str, Option<bool>, Option<bool>); 6] = [
+        (
+            "security.advanced_security",
+            desired.advanced_security,
+            current.and_then(|c| c.advanced_security),
+        ),
+        (
+            "security.secret_scanning",
+            desired.secret_scanning,
+            current.and_then(|c| c.secret_scanning),
+        ),
+        (
+            "security.secret_scanning_push_protection",
+            desired.secret_scanning_push_protection,
+            current.and_then(|c| c.secret_scanning_push_protection),
+        ),
+        (
+            "security.dependabot_security_updates",
+            desired.dependabot_security_updates,
+            current.and_then(|c| c.dependabot_security_updates),
+        ),
+        (
+            "security.vulnerability_alerts",
+            desired.vulnerability_alerts,
+            current.and_then(|c| c.vulnerability_alerts),
+        ),
+        (
+            "security.automated_security_fixes",
+            desired.automated_security_fixes,
+            current.and_then(|c| c.automated_security_fixes),
+        ),
+    ];
+    for (field, want, have) in fields {
+        match (want, have) {
+            (Some(_), None) => unavailable.push(field),
+            (want, have) => push_if_changed(
+                changes,
+                SettingCategory::Security,
+                field,
+                want.as_ref(),
+                have.as_ref(),
+            ),
+        }
+    }
+}
+
+fn push_if_changed<T: PartialEq + Serialize>(
+    changes: &mut Vec<SettingChange>,
+    category: SettingCategory,
+    field: &'static str,
+    want: Option<&T>,
+    have: Option<&T>,
+) {
+    if let Some(target) = want
+        && have != Some(target)
+    {
+        changes.push(SettingChange {
+            category,
+            field,
+            current: have.map(setting_value),
+            desired: setting_value(target),
+        });
+    }
+}
+
+/// Renders a setting the way it is written in config files (e.g. `local_only`, not `LocalOnly`).
+fn setting_value<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        Ok(other) => other.to_string(),
+        Err(_) => "unknown".to_string(),
+    }
+}
 
-    RepoSettingsDiff { changes }
+fn format_title_message<T: std::fmt::Debug, M: std::fmt::Debug>(
+    title: Option<&T>,
+    msg: Option<&M>,
+) -> String {
+    format!(
+        "{} / {}",
+        title
+            .map(|v| format!("{:?}", v))
+            .unwrap_or_else(|| "unset".to_string()),
+        msg.map(|v| format!("{:?}", v))
+            .unwrap_or_else(|| "unset".to_string())
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::settings::OrgBasePermission;
 
     fn lbl(name: &str, color: Option<&str>, desc: Option<&str>) -> LabelSpec {
         LabelSpec {
@@ -228,6 +1098,8 @@ mod tests {
                 squash_merge_option: None,
             }),
             branch_protection: None,
+            actions: None,
+            security: None,
         };
         let current = RepoSettings {
             pull_requests: Some(crate::settings::PullRequestSettings {
@@ -240,6 +1112,8 @@ mod tests {
                 squash_merge_option: None,
             }),
             branch_protection: None,
+            actions: None,
+            security: None,
         };
 
         let diff = diff_repo_settings(&desired, &current);
@@ -279,6 +1153,8 @@ mod tests {
                 ),
             }),
             branch_protection: None,
+            actions: None,
+            security: None,
         };
         let current = RepoSettings {
             pull_requests: Some(crate::settings::PullRequestSettings {
@@ -291,6 +1167,8 @@ mod tests {
                 squash_merge_option: None,
             }),
             branch_protection: None,
+            actions: None,
+            security: None,
         };
 
         let diff = diff_repo_settings(&desired, &current);
@@ -306,4 +1184,530 @@ mod tests {
                 .any(|c| c.field == "merge_commit_message_option")
         );
     }
+
+    #[test]
+    fn matching_squash_and_merge_options_are_unchanged() {
+        let pr = crate::settings::PullRequestSettings {
+            allow_merge_commit: None,
+            allow_squash_merge: None,
+            allow_rebase_merge: None,
+            allow_auto_merge: None,
+            delete_branch_on_merge: None,
+            merge_commit_message_option: Some(
+                crate::settings::MergeCommitMessageOption::PullRequestTitle,
+            ),
+            squash_merge_option: Some(crate::settings::SquashMergeOption::DefaultMessage),
+        };
+        let desired = RepoSettings {
+            pull_requests: Some(pr.clone()),
+            branch_protection: None,
+            actions: None,
+            security: None,
+        };
+        let current = RepoSettings {
+            pull_requests: Some(crate::settings::PullRequestSettings {
+                squash_merge_option: Some(crate::settings::SquashMergeOption::PullRequestTitle),
+                ..pr
+            }),
+            branch_protection: None,
+            actions: None,
+            security: None,
+        };
+
+        let diff = diff_repo_settings(&desired, &current);
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].field, "squash_merge_option");
+        assert_eq!(diff.changes[0].current.as_deref(), Some("PrTitle / Blank"));
+        assert_eq!(diff.changes[0].desired, "CommitOrPrTitle / CommitMessages");
+    }
+
+    #[test]
+    fn computes_actions_settings_diff() {
+        use crate::settings::{AllowedActions, SelectedActions, WorkflowPermissions};
+
+        let desired = RepoSettings {
+            actions: Some(ActionsSettings {
+                enabled: Some(true),
+                allowed_actions: Some(AllowedActions::Selected),
+                selected_actions: Some(SelectedActions {
+                    github_owned_allowed: Some(true),
+                    verified_allowed: None,
+                    patterns_allowed: Some(vec!["b/*".to_string(), "a/*".to_string()]),
+                }),
+                default_workflow_permissions: Some(WorkflowPermissions::Read),
+                can_approve_pull_request_reviews: Some(false),
+                fork_pr_approval: None,
+            }),
+            ..Default::default()
+        };
+        let current = RepoSettings {
+            actions: Some(ActionsSettings {
+                enabled: Some(true),
+                allowed_actions: Some(AllowedActions::All),
+                selected_actions: Some(SelectedActions {
+                    github_owned_allowed: Some(true),
+                    verified_allowed: Some(true),
+                    patterns_allowed: Some(vec!["a/*".to_string(), "b/*".to_string()]),
+                }),
+                default_workflow_permissions: Some(WorkflowPermissions::Write),
+                can_approve_pull_request_reviews: Some(false),
+                fork_pr_approval: None,
+            }),
+            ..Default::default()
+        };
+
+        let diff = diff_repo_settings(&desired, &current);
+        assert_eq!(diff.changes.len(), 2);
+        assert!(
+            diff.changes
+                .iter()
+                .all(|c| c.category == SettingCategory::Actions)
+        );
+        assert!(
+            diff.changes
+                .iter()
+                .any(|c| c.field == "actions.allowed_actions"
+                    && c.current.as_deref() == Some("all")
+                    && c.desired == "selected")
+        );
+        assert!(
+            diff.changes
+                .iter()
+                .any(|c| c.field == "actions.default_workflow_permissions"
+                    && c.current.as_deref() == Some("write")
+                    && c.desired == "read")
+        );
+    }
+
+    #[test]
+    fn reports_unavailable_security_features() {
+        let desired = RepoSettings {
+            security: Some(SecuritySettings {
+                advanced_security: Some(true),
+                secret_scanning: Some(true),
+                vulnerability_alerts: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let current = RepoSettings {
+            security: Some(SecuritySettings {
+                advanced_security: None,
+                secret_scanning: Some(false),
+                vulnerability_alerts: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let diff = diff_repo_settings(&desired, &current);
+        assert_eq!(diff.unavailable, vec!["security.advanced_security"]);
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].field, "security.secret_scanning");
+    }
+
+    #[test]
+    fn computes_access_diff_respecting_mode() {
+        let mut desired = AccessConfig::default();
+        desired
+            .teams
+            .insert("platform".to_string(), Permission::Maintain);
+        desired
+            .collaborators
+            .insert("Octocat".to_string(), Permission::Push);
+        let mut current = AccessConfig::default();
+        current
+            .teams
+            .insert("platform".to_string(), Permission::Push);
+        current
+            .teams
+            .insert("legacy".to_string(), Permission::Admin);
+        current
+            .collaborators
+            .insert("octocat".to_string(), Permission::Push);
+
+        let diff = diff_access(&desired, &current);
+        assert!(diff.to_add.is_empty());
+        assert_eq!(diff.to_update.len(), 1);
+        assert_eq!(diff.to_update[0].name, "platform");
+        assert_eq!(diff.to_update[0].current, Some(Permission::Push));
+        assert!(diff.to_remove.is_empty());
+
+        desired.mode = Some(ManageMode::Exclusive);
+        let diff = diff_access(&desired, &current);
+        assert_eq!(diff.to_remove.len(), 1);
+        assert_eq!(diff.to_remove[0].name, "legacy");
+        assert_eq!(diff.to_remove[0].kind, AccessKind::Team);
+    }
+
+    #[test]
+    fn computes_webhook_diff_by_url() {
+        let hook = |url: &str, events: &[&str], active: bool| WebhookSpec {
+            url: url.to_string(),
+            content_type: None,
+            events: Some(events.iter().map(|e| e.to_string()).collect()),
+            active: Some(active),
+            insecure_ssl: None,
+            secret_env: None,
+        };
+        let mut desired = WebhooksConfig {
+            mode: None,
+            hooks: vec![
+                hook(
+                    "https://ci.example.com/hook",
+                    &["push", "pull_request"],
+                    true,
+                ),
+                hook("https://new.example.com/hook", &["push"], true),
+            ],
+        };
+        let current = vec![
+            RepoWebhook {
+                id: 1,
+                spec: hook(
+                    "https://ci.example.com/hook",
+                    &["pull_request", "push"],
+                    false,
+                ),
+                has_secret: true,
+            },
+            RepoWebhook {
+                id: 2,
+                spec: hook("https://manual.example.com/hook", &["push"], true),
+                has_secret: false,
+            },
+        ];
+
+        let diff = diff_webhooks(&desired, &current);
+        assert_eq!(diff.to_add.len(), 1);
+        assert_eq!(diff.to_add[0].url, "https://new.example.com/hook");
+        assert_eq!(diff.to_update.len(), 1);
+        assert_eq!(diff.to_update[0].id, 1);
+        // Only `active` changed, so the config (and its secret) is left alone.
+        assert!(!diff.to_update[0].config_changed);
+        assert!(!diff.to_update[0].loses_secret());
+        assert!(diff.to_remove.is_empty());
+
+        desired.hooks[0].content_type = Some(crate::settings::WebhookContentType::Json);
+        let diff = diff_webhooks(&desired, &current);
+        assert!(diff.to_update[0].loses_secret());
+        desired.hooks[0].secret_env = Some("CI_HOOK_SECRET".to_string());
+        let diff = diff_webhooks(&desired, &current);
+        assert!(diff.to_update[0].config_changed);
+        assert!(!diff.to_update[0].loses_secret());
+
+        desired.mode = Some(ManageMode::Exclusive);
+        let diff = diff_webhooks(&desired, &current);
+        assert_eq!(
+            diff.to_remove,
+            vec![(2, "https://manual.example.com/hook".to_string())]
+        );
+    }
+
+    #[test]
+    fn computes_environment_changes() {
+        use crate::settings::{DeploymentBranchPolicy, EnvironmentReviewers};
+
+        let desired = EnvironmentsConfig {
+            environments: vec![
+                EnvironmentSpec {
+                    name: "production".to_string(),
+                    wait_timer: Some(30),
+                    reviewers: Some(EnvironmentReviewers {
+                        users: vec![],
+                        teams: vec!["ops".to_string()],
+                    }),
+                    deployment_branch_policy: Some(DeploymentBranchPolicy {
+                        protected_branches: false,
+                        custom_branches: vec!["release/*".to_string(), "main".to_string()],
+                    }),
+                    ..Default::default()
+                },
+                EnvironmentSpec {
+                    name: "staging".to_string(),
+                    ..Default::default()
+                },
+            ],
+        };
+        let current = vec![EnvironmentSpec {
+            name: "production".to_string(),
+            wait_timer: Some(30),
+            reviewers: Some(EnvironmentReviewers {
+                users: vec![],
+                teams: vec!["ops".to_string()],
+            }),
+            deployment_branch_policy: Some(DeploymentBranchPolicy {
+                protected_branches: false,
+                custom_branches: vec!["main".to_string()],
+            }),
+            ..Default::default()
+        }];
+
+        let changes = diff_environments(&desired, &current);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].spec.name, "production");
+        assert!(changes[0].exists);
+        assert_eq!(changes[0].fields, vec!["deployment_branch_policy"]);
+        assert_eq!(changes[1].spec.name, "staging");
+        assert!(!changes[1].exists);
+
+        // "Any branch" is reported by GitHub as no policy.
+        let any_branch = EnvironmentsConfig {
+            environments: vec![EnvironmentSpec {
+                name: "preview".to_string(),
+                deployment_branch_policy: Some(DeploymentBranchPolicy::default()),
+                ..Default::default()
+            }],
+        };
+        let current = vec![EnvironmentSpec {
+            name: "preview".to_string(),
+            ..Default::default()
+        }];
+        assert!(diff_environments(&any_branch, &current).is_empty());
+    }
+
+    #[test]
+    fn replaces_changed_autolinks() {
+        let link = |prefix: &str, template: &str| AutolinkSpec {
+            key_prefix: prefix.to_string(),
+            url_template: template.to_string(),
+            is_alphanumeric: None,
+        };
+        let desired = AutolinksConfig {
+            mode: Some(ManageMode::Exclusive),
+            autolinks: vec![
+                link("JIRA-", "https://jira.example.com/browse/JIRA-<num>"),
+                link("OPS-", "https://ops.example.com/<num>"),
+            ],
+        };
+        let current = vec![
+            RepoAutolink {
+                id: 1,
+                spec: link("JIRA-", "https://old-jira.example.com/browse/JIRA-<num>"),
+            },
+            RepoAutolink {
+                id: 2,
+                spec: AutolinkSpec {
+                    is_alphanumeric: Some(true),
+                    ..link("OPS-", "https://ops.example.com/<num>")
+                },
+            },
+            RepoAutolink {
+                id: 3,
+                spec: link("OLD-", "https://old.example.com/<num>"),
+            },
+        ];
+
+        let diff = diff_autolinks(&desired, &current);
+        assert!(diff.to_add.is_empty());
+        assert_eq!(diff.to_replace.len(), 1);
+        assert_eq!(diff.to_replace[0].0, 1);
+        assert_eq!(diff.to_remove.len(), 1);
+        assert_eq!(diff.to_remove[0].0, 3);
+    }
+
+    #[test]
+    fn diffs_only_declared_properties() {
+        let single = |v: &str| PropertyValue::Single(v.to_string());
+        let multi =
+            |vs: &[&str]| PropertyValue::Multiple(vs.iter().map(|v| v.to_string()).collect());
+        let desired: CustomProperties = [
+            ("tier".to_string(), single("critical")),
+            ("team".to_string(), single("platform")),
+            ("regions".to_string(), multi(&["eu", "us"])),
+        ]
+        .into_iter()
+        .collect();
+        let current: CustomProperties = [
+            ("tier".to_string(), single("low")),
+            ("regions".to_string(), multi(&["us", "eu"])),
+            ("owner".to_string(), single("someone")),
+        ]
+        .into_iter()
+        .collect();
+
+        let changes = diff_properties(&desired, &current);
+        let names: Vec<_> = changes.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["team", "tier"]);
+        assert_eq!(changes[1].current, Some(single("low")));
+    }
+
+    #[test]
+    fn org_two_factor_is_reported_separately() {
+        let desired = OrgSettings {
+            default_repository_permission: Some(OrgBasePermission::Read),
+            members_can_create_pages: Some(false),
+            two_factor_requirement_enabled: Some(true),
+            ..Default::default()
+        };
+        let current = OrgSettings {
+            default_repository_permission: Some(OrgBasePermission::Write),
+            members_can_create_pages: Some(false),
+            two_factor_requirement_enabled: Some(false),
+            ..Default::default()
+        };
+
+        let diff = diff_org_settings(&desired, &current);
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].field, "default_repository_permission");
+        assert_eq!(diff.changes[0].current.as_deref(), Some("write"));
+        assert_eq!(diff.changes[0].desired, "read");
+        assert_eq!(
+            diff.two_factor.map(|c| c.field),
+            Some("two_factor_requirement_enabled")
+        );
+    }
+
+    #[test]
+    fn diffs_team_tree_and_memberships() {
+        let team = |name: &str, parent: Option<&str>| TeamSpec {
+            name: name.to_string(),
+            parent: parent.map(|p| p.to_string()),
+            ..Default::default()
+        };
+        let desired = TeamsConfig {
+            teams: vec![
+                TeamSpec {
+                    maintainers: vec!["alice".to_string()],
+                    members: vec!["bob".to_string()],
+                    ..team("Platform", None)
+                },
+                team("Platform SRE", Some("Platform")),
+                TeamSpec {
+                    renamed_from: Some("web".to_string()),
+                    ..team("Frontend", None)
+                },
+                team("Infra", None),
+            ],
+        };
+        let current = vec![
+            OrgTeam {
+                slug: "platform".to_string(),
+                spec: TeamSpec {
+                    maintainers: vec!["Alice".to_string()],
+                    members: vec!["carol".to_string()],
+                    ..team("Platform", None)
+                },
+            },
+            OrgTeam {
+                slug: "web".to_string(),
+                spec: team("Web", Some("Platform")),
+            },
+            OrgTeam {
+                slug: "legacy".to_string(),
+                spec: team("Legacy", None),
+            },
+            OrgTeam {
+                slug: "legacy-ops".to_string(),
+                spec: team("Legacy Ops", Some("Legacy")),
+            },
+        ];
+
+        let diff = diff_teams(&desired, &current);
+        let created: Vec<_> = diff.to_create.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(created, vec!["Infra", "Platform SRE"]);
+        assert_eq!(diff.to_update.len(), 1);
+        assert_eq!(diff.to_update[0].slug, "web");
+        // Frontend declares no parent, so it moves to the top level.
+        assert_eq!(diff.to_update[0].fields, vec!["name", "parent"]);
+        let memberships: Vec<_> = diff
+            .memberships
+            .iter()
+            .map(|m| (m.user.as_str(), m.desired))
+            .collect();
+        assert_eq!(
+            memberships,
+            vec![("bob", Some(TeamRole::Member)), ("carol", None)]
+        );
+        assert_eq!(diff.unmanaged, vec!["legacy"]);
+    }
+
+    #[test]
+    fn blocks_removal_of_milestones_with_open_issues() {
+        let milestone = |title: &str, due_on: Option<&str>| MilestoneSpec {
+            title: title.to_string(),
+            description: None,
+            due_on: due_on.map(|d| d.to_string()),
+            state: None,
+        };
+        let existing = |number: u64, open_issues: u64, spec: MilestoneSpec| RepoMilestone {
+            number,
+            open_issues,
+            spec,
+        };
+        let mut desired = MilestonesConfig {
+            mode: Some(ManageMode::Exclusive),
+            milestones: vec![
+                milestone("2026-Q4", Some("2026-12-31")),
+                milestone("2027-Q1", Some("2027-03-31")),
+            ],
+        };
+        let current = vec![
+            existing(1, 0, milestone("2026-Q4", Some("2026-12-15"))),
+            existing(2, 3, milestone("2026-Q2", None)),
+            existing(3, 0, milestone("2026-Q3", None)),
+            existing(4, 0, milestone("2026-Q1", None)),
+        ];
+
+        let diff = diff_milestones(&desired, &current);
+        assert_eq!(diff.to_add, vec![milestone("2027-Q1", Some("2027-03-31"))]);
+        assert_eq!(diff.to_update.len(), 1);
+        assert_eq!(diff.to_update[0].0, 1);
+        let removed: Vec<_> = diff.to_remove.iter().map(|m| m.number).collect();
+        assert_eq!(removed, vec![4, 3]);
+        let blocked: Vec<_> = diff.blocked.iter().map(|m| m.number).collect();
+        assert_eq!(blocked, vec![2]);
+
+        desired.mode = None;
+        let diff = diff_milestones(&desired, &current);
+        assert!(diff.to_remove.is_empty());
+        assert!(diff.blocked.is_empty());
+    }
+
+    #[test]
+    fn blocks_removal_of_categories_with_discussions() {
+        let category = |name: &str, emoji: &str| DiscussionCategorySpec {
+            name: name.to_string(),
+            emoji: Some(emoji.to_string()),
+            description: None,
+            format: None,
+        };
+        let existing =
+            |id: &str, discussions: u64, spec: DiscussionCategorySpec| RepoDiscussionCategory {
+                id: id.to_string(),
+                discussions,
+                spec,
+            };
+        let desired = DiscussionsConfig {
+            enabled: Some(true),
+            mode: Some(ManageMode::Exclusive),
+            categories: vec![
+                category("General", ":speech_balloon:"),
+                DiscussionCategorySpec {
+                    format: Some(DiscussionFormat::Question),
+                    ..category("Q&A", ":pray:")
+                },
+                category("RFCs", ":memo:"),
+            ],
+        };
+        let current = RepoDiscussions {
+            enabled: false,
+            categories: vec![
+                existing("1", 4, category("General", ":speech_balloon:")),
+                existing("2", 0, category("Q&A", ":pray:")),
+                existing("3", 2, category("Ideas", ":bulb:")),
+                existing("4", 0, category("Polls", ":ballot_box:")),
+            ],
+        };
+
+        let diff = diff_discussions(&desired, &current);
+        assert_eq!(diff.enable, Some(true));
+        assert_eq!(diff.to_add, vec![category("RFCs", ":memo:")]);
+        assert_eq!(diff.to_update.len(), 1);
+        assert_eq!(diff.to_update[0].1, vec!["format"]);
+        let removed: Vec<_> = diff.to_remove.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(removed, vec!["4"]);
+        let blocked: Vec<_> = diff.blocked.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(blocked, vec!["3"]);
+    }
 }
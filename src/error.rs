@@ -47,7 +47,7 @@ pub enum Error {
     #[error("glob error reading paths: {0}")]
     GlobGlob(#[from] glob::GlobError),
     #[error("github api error: {0}")]
-    Octo(#[from] octocrab::Error),
+    Octo(Box<octocrab::Error>),
     #[error("repository '{org}/{repo}' not found")]
     RepoNotFound { org: String, repo: String },
     #[error("repo '{repo}' has conflicting config: {reason}")]
@@ -56,6 +56,32 @@ pub enum Error {
     IoSimple(#[from] std::io::Error),
     #[error("invalid arguments: {0}")]
     InvalidArgs(String),
+    #[error("environment variable '{0}' referenced in config is not set")]
+    MissingEnv(String),
+    #[error(
+        "webhook '{url}' in '{repo}' has a secret that updating its config would remove; set secret_env for it"
+    )]
+    WebhookSecretRequired { repo: String, url: String },
+    #[error("invalid issue template '{path}' in set '{set}': {reason}")]
+    InvalidIssueTemplate {
+        set: String,
+        path: String,
+        reason: String,
+    },
+    #[error(
+        "'{repo}' has {count} discussion category changes; GitHub has no API for them, so make them in the repository's Discussions settings"
+    )]
+    DiscussionCategoriesDiffer { repo: String, count: usize },
+    #[error("github graphql error: {0}")]
+    GraphQl(String),
+    #[error("{0} repositories or org-wide steps failed; see the summary above")]
+    ReposFailed(usize),
+    #[error("push to '{branch}' in '{repo}' was rejected: {reason}")]
+    ProtectedBranch {
+        repo: String,
+        branch: String,
+        reason: String,
+    },
 }
 
 impl Error {
@@ -87,3 +113,10 @@ impl Error {
         }
     }
 }
+
+// Boxed because octocrab's error is several times larger than every other variant.
+impl From<octocrab::Error> for Error {
+    fn from(source: octocrab::Error) -> Self {
+        Error::Octo(Box::new(source))
+    }
+}
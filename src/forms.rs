@@ -0,0 +1,310 @@
+use std::collections::HashSet;
+
+use serde_yaml::{Mapping, Value};
+
+const ELEMENT_TYPES: &[&str] = &["markdown", "textarea", "input", "dropdown", "checkboxes"];
+
+/// Whether `path` is an issue form, i.e. a YAML file under `.github/ISSUE_TEMPLATE/` other
+/// than the chooser `config.yml`.
+pub fn is_issue_form(path: &str) -> bool {
+    path.contains(".github/ISSUE_TEMPLATE/")
+        && (path.ends_with(".yml") || path.ends_with(".yaml"))
+        && !path.ends_with("/config.yml")
+        && !path.ends_with("/config.yaml")
+}
+
+/// Whether `path` is an issue form or a Markdown issue template.
+pub fn is_issue_template(path: &str) -> bool {
+    is_issue_form(path) || is_markdown_template(path)
+}
+
+/// Whether `path` is a legacy Markdown issue template with YAML front matter.
+pub fn is_markdown_template(path: &str) -> bool {
+    path.contains(".github/ISSUE_TEMPLATE/") && path.ends_with(".md")
+}
+
+/// Validates an issue form or Markdown template and returns the labels it applies.
+pub fn validate_issue_template(path: &str, contents: &str) -> Result<Vec<String>, String> {
+    if is_markdown_template(path) {
+        validate_markdown_template(contents)
+    } else {
+        validate_issue_form(contents)
+    }
+}
+
+/// Name and description shown in the template chooser: `name`/`description` for forms,
+/// `name`/`about` from the front matter of Markdown templates.
+pub fn template_summary(path: &str, contents: &str) -> (Option<String>, Option<String>) {
+    let (doc, description_key) = if is_markdown_template(path) {
+        (front_matter(contents).and_then(parse_mapping), "about")
+    } else {
+        (parse_mapping(contents), "description")
+    };
+    let field = |key: &str| {
+        doc.as_ref()
+            .and_then(|d| get(d, key))
+            .and_then(Value::as_str)
+            .map(|s| s.to_string())
+    };
+    (field("name"), field(description_key))
+}
+
+fn validate_markdown_template(contents: &str) -> Result<Vec<String>, String> {
+    let raw = front_matter(contents)
+        .ok_or_else(|| "missing YAML front matter delimited by '---'".to_string())?;
+    let doc: Value = serde_yaml::from_str(raw).map_err(|e| format!("invalid front matter: {e}"))?;
+    let doc = doc
+        .as_mapping()
+        .ok_or_else(|| "front matter must be a mapping".to_string())?;
+    for key in ["name", "about"] {
+        match get(doc, key) {
+            Some(Value::String(s)) if !s.trim().is_empty() => {}
+            Some(_) => return Err(format!("front matter '{key}' must be a non-empty string")),
+            None => return Err(format!("front matter is missing '{key}'")),
+        }
+    }
+    if !matches!(get(doc, "title"), None | Some(Value::String(_))) {
+        return Err("front matter 'title' must be a string".to_string());
+    }
+    for key in ["labels", "assignees"] {
+        match get(doc, key) {
+            None | Some(Value::String(_)) => {}
+            Some(Value::Sequence(items)) if items.iter().all(Value::is_string) => {}
+            Some(_) => {
+                return Err(format!(
+                    "front matter '{key}' must be a string or a list of strings"
+                ));
+            }
+        }
+    }
+    Ok(form_labels(doc))
+}
+
+/// The YAML between a leading `---` line and the next `---` line.
+fn front_matter(contents: &str) -> Option<&str> {
+    let rest = contents.strip_prefix("---")?;
+    let rest = rest
+        .strip_prefix("\r\n")
+        .or_else(|| rest.strip_prefix('\n'))?;
+    if rest.starts_with("---") {
+        return Some("");
+    }
+    let end = rest.find("\n---")?;
+    Some(&rest[..end])
+}
+
+fn parse_mapping(contents: &str) -> Option<Mapping> {
+    serde_yaml::from_str::<Value>(contents)
+        .ok()
+        .and_then(|v| v.as_mapping().cloned())
+}
+
+/// Checks an issue form against GitHub's form schema and returns the labels it applies.
+pub fn validate_issue_form(contents: &str) -> Result<Vec<String>, String> {
+    let doc: Value = serde_yaml::from_str(contents).map_err(|e| format!("invalid yaml: {e}"))?;
+    let doc = doc
+        .as_mapping()
+        .ok_or_else(|| "top level must be a mapping".to_string())?;
+
+    for key in ["name", "description"] {
+        match get(doc, key) {
+            Some(Value::String(s)) if !s.trim().is_empty() => {}
+            Some(_) => return Err(format!("'{key}' must be a non-empty string")),
+            None => return Err(format!("missing required key '{key}'")),
+        }
+    }
+
+    let body = match get(doc, "body") {
+        Some(Value::Sequence(items)) if !items.is_empty() => items,
+        Some(_) => return Err("'body' must be a non-empty list".to_string()),
+        None => return Err("missing required key 'body'".to_string()),
+    };
+
+    let mut ids = HashSet::new();
+    let mut labels = HashSet::new();
+    let mut has_input = false;
+    for (idx, element) in body.iter().enumerate() {
+        let at = format!("body[{idx}]");
+        let element = element
+            .as_mapping()
+            .ok_or_else(|| format!("{at} must be a mapping"))?;
+        let kind = get(element, "type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("{at} is missing 'type'"))?;
+        if !ELEMENT_TYPES.contains(&kind) {
+            return Err(format!(
+                "{at} has unknown type '{kind}' (expected one of {})",
+                ELEMENT_TYPES.join(", ")
+            ));
+        }
+
+        if let Some(id) = get(element, "id") {
+            let id = id
+                .as_str()
+                .ok_or_else(|| format!("{at} 'id' must be a string"))?;
+            if id.is_empty()
+                || !id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(format!(
+                    "{at} id '{id}' may only contain letters, digits, '-' and '_'"
+                ));
+            }
+            if !ids.insert(id.to_string()) {
+                return Err(format!("{at} reuses id '{id}'"));
+            }
+        }
+
+        let attributes = get(element, "attributes")
+            .and_then(Value::as_mapping)
+            .ok_or_else(|| format!("{at} ({kind}) is missing 'attributes'"))?;
+        if kind == "markdown" {
+            require_string(attributes, "value", &at, kind)?;
+            continue;
+        }
+        has_input = true;
+        let label = require_string(attributes, "label", &at, kind)?;
+        if !labels.insert(label.to_string()) {
+            return Err(format!("{at} reuses label '{label}'"));
+        }
+        if kind == "dropdown" || kind == "checkboxes" {
+            match get(attributes, "options") {
+                Some(Value::Sequence(options)) if !options.is_empty() => {
+                    if kind == "checkboxes"
+                        && options
+                            .iter()
+                            .any(|o| o.as_mapping().and_then(|m| get(m, "label")).is_none())
+                    {
+                        return Err(format!(
+                            "{at} (checkboxes) options must each have a 'label'"
+                        ));
+                    }
+                }
+                _ => return Err(format!("{at} ({kind}) needs a non-empty 'options' list")),
+            }
+        }
+    }
+    if !has_input {
+        return Err("'body' must contain at least one non-markdown element".to_string());
+    }
+
+    Ok(form_labels(doc))
+}
+
+fn form_labels(doc: &Mapping) -> Vec<String> {
+    match get(doc, "labels") {
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect(),
+        Some(Value::Sequence(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|l| l.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn get<'a>(map: &'a Mapping, key: &str) -> Option<&'a Value> {
+    map.get(Value::String(key.to_string()))
+}
+
+fn require_string<'a>(
+    map: &'a Mapping,
+    key: &str,
+    at: &str,
+    kind: &str,
+) -> Result<&'a str, String> {
+    match get(map, key).and_then(Value::as_str) {
+        Some(s) if !s.trim().is_empty() => Ok(s),
+        _ => Err(format!("{at} ({kind}) is missing attributes.{key}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"
+name: Bug report
+description: Something is broken
+labels: [bug, "needs triage"]
+body:
+  - type: markdown
+    attributes:
+      value: Thanks for reporting!
+  - type: textarea
+    id: what-happened
+    attributes:
+      label: What happened?
+  - type: dropdown
+    id: version
+    attributes:
+      label: Version
+      options: ["1.0", "2.0"]
+"#;
+
+    #[test]
+    fn accepts_valid_form_and_returns_labels() {
+        assert_eq!(
+            validate_issue_form(VALID),
+            Ok(vec!["bug".to_string(), "needs triage".to_string()])
+        );
+    }
+
+    #[test]
+    fn rejects_missing_description_and_bad_elements() {
+        let no_description = VALID.replace("description: Something is broken\n", "");
+        assert_eq!(
+            validate_issue_form(&no_description),
+            Err("missing required key 'description'".to_string())
+        );
+
+        let bad_type = VALID.replace("type: dropdown", "type: select");
+        assert!(
+            validate_issue_form(&bad_type)
+                .unwrap_err()
+                .contains("unknown type 'select'")
+        );
+
+        let duplicate_id = VALID.replace("id: version", "id: what-happened");
+        assert_eq!(
+            validate_issue_form(&duplicate_id),
+            Err("body[2] reuses id 'what-happened'".to_string())
+        );
+    }
+
+    #[test]
+    fn validates_markdown_front_matter() {
+        let template = "---\nname: Bug\nabout: Report a bug\ntitle: '[Bug] '\nlabels: bug, triage\n---\n\n## Steps\n";
+        assert_eq!(
+            validate_issue_template(".github/ISSUE_TEMPLATE/bug.md", template),
+            Ok(vec!["bug".to_string(), "triage".to_string()])
+        );
+        assert_eq!(
+            template_summary(".github/ISSUE_TEMPLATE/bug.md", template),
+            (Some("Bug".to_string()), Some("Report a bug".to_string()))
+        );
+        assert_eq!(
+            validate_issue_template(".github/ISSUE_TEMPLATE/bug.md", "## Steps\n"),
+            Err("missing YAML front matter delimited by '---'".to_string())
+        );
+        assert_eq!(
+            validate_issue_template(
+                ".github/ISSUE_TEMPLATE/bug.md",
+                "---\nname: Bug\n---\nbody\n"
+            ),
+            Err("front matter is missing 'about'".to_string())
+        );
+    }
+
+    #[test]
+    fn detects_issue_forms_by_path() {
+        assert!(is_issue_form(".github/ISSUE_TEMPLATE/bug.yml"));
+        assert!(!is_issue_form(".github/ISSUE_TEMPLATE/config.yml"));
+        assert!(!is_issue_form(".github/DISCUSSION_TEMPLATE/ideas.yml"));
+    }
+}
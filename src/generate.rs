@@ -5,9 +5,10 @@ use std::path::Path;
 
 use crate::config::{RepoConfig, RootConfig};
 use crate::error::Result;
+use crate::forms::is_issue_template;
 use crate::github::GithubClient;
 use crate::sets::{IssueTemplateFile, LabelSpec};
-use crate::settings::RepoSettings;
+use crate::settings::{AccessConfig, EnvironmentsConfig, RepoSettings, WebhooksConfig};
 
 #[derive(Clone)]
 struct RepoSnapshot {
@@ -15,6 +16,9 @@ struct RepoSnapshot {
     labels: Vec<LabelSpec>,
     settings: Option<RepoSettings>,
     templates: Vec<IssueTemplateFile>,
+    access: Option<AccessConfig>,
+    webhooks: Option<WebhooksConfig>,
+    environments: Option<EnvironmentsConfig>,
 }
 
 fn group_signatures<T: Serialize + Clone>(
@@ -84,6 +88,8 @@ pub async fn generate_configs(
         default_sets: Vec::new(),
         repos: Vec::new(),
         config_sets_dir: None,
+        repo_selectors: Vec::new(),
+        pull_request: None,
     };
 
     let sets_root = output_base.join("config-sets");
@@ -108,6 +114,28 @@ pub async fn generate_configs(
         }
     }
 
+    // Access is repo-specific by nature, so it is never folded into the core set.
+    let access_groups = group_signatures(snapshots.iter().filter_map(|s| {
+        s.access
+            .as_ref()
+            .filter(|a| !a.teams.is_empty() || !a.collaborators.is_empty())
+            .map(|a| (s.name.clone(), a.clone()))
+    }));
+
+    let webhook_groups = group_signatures(snapshots.iter().filter_map(|s| {
+        s.webhooks
+            .as_ref()
+            .filter(|w| !w.hooks.is_empty())
+            .map(|w| (s.name.clone(), w.clone()))
+    }));
+
+    let environment_groups = group_signatures(snapshots.iter().filter_map(|s| {
+        s.environments
+            .as_ref()
+            .filter(|e| !e.environments.is_empty())
+            .map(|e| (s.name.clone(), e.clone()))
+    }));
+
     // Remove core items
     let mut residuals = Vec::new();
     for snap in snapshots {
@@ -175,6 +203,34 @@ pub async fn generate_configs(
         |set_name, payload| write_set(&sets_root.join(set_name), &[], Some(payload), &[], format),
     )?;
 
+    create_component_sets(
+        "access",
+        &access_groups,
+        &mut used_names,
+        &mut set_mapping,
+        |set_name, payload| write_named_file(&sets_root.join(set_name), "access", payload, format),
+    )?;
+
+    create_component_sets(
+        "webhooks",
+        &webhook_groups,
+        &mut used_names,
+        &mut set_mapping,
+        |set_name, payload| {
+            write_named_file(&sets_root.join(set_name), "webhooks", payload, format)
+        },
+    )?;
+
+    create_component_sets(
+        "environments",
+        &environment_groups,
+        &mut used_names,
+        &mut set_mapping,
+        |set_name, payload| {
+            write_named_file(&sets_root.join(set_name), "environments", payload, format)
+        },
+    )?;
+
     for (repo_name, _, _, _) in residuals {
         let mut sets = set_mapping.remove(&repo_name).unwrap_or_default();
         sets.sort();
@@ -184,6 +240,9 @@ pub async fn generate_configs(
         root.repos.push(RepoConfig {
             name: repo_name,
             sets,
+            properties: Default::default(),
+            pull_request: None,
+            file_delivery: None,
         });
     }
 
@@ -205,6 +264,8 @@ async fn fetch_repo(gh: &GithubClient, repo: &str) -> Result<RepoSnapshot> {
 
     let labels = gh.list_repo_labels(repo).await?;
     let mut settings = gh.get_repo_settings(repo).await?;
+    settings.actions = gh.get_actions_settings(repo).await?;
+    settings.security = Some(gh.get_security_settings(repo).await?);
 
     let mut bp_rules = Vec::new();
     for branch in gh.list_branches(repo).await.unwrap_or_default() {
@@ -217,12 +278,30 @@ async fn fetch_repo(gh: &GithubClient, repo: &str) -> Result<RepoSnapshot> {
             Some(crate::settings::BranchProtectionConfig { rules: bp_rules });
     }
 
+    let access = gh.get_access(repo).await?;
+    // Secrets cannot be read back; add `secret_env` to the generated hooks by hand.
+    let webhooks = WebhooksConfig {
+        mode: None,
+        hooks: gh
+            .list_webhooks(repo)
+            .await?
+            .into_iter()
+            .map(|h| h.spec)
+            .collect(),
+    };
+    let environments = EnvironmentsConfig {
+        environments: gh.list_environments(repo).await?,
+    };
+
     let mut templates = Vec::new();
     let paths = gh
         .list_github_files(repo, &default_branch, ".github/ISSUE_TEMPLATE/")
         .await
         .unwrap_or_default();
-    for path in paths {
+    // Only files that sets can load: forms, Markdown templates and config.yml.
+    for path in paths.into_iter().filter(|p| {
+        is_issue_template(p) || p.ends_with("/config.yml") || p.ends_with("/config.yaml")
+    }) {
         if let Some(file) = gh.get_file(repo, &path, Some(&default_branch)).await? {
             templates.push(IssueTemplateFile {
                 path,
@@ -243,6 +322,9 @@ async fn fetch_repo(gh: &GithubClient, repo: &str) -> Result<RepoSnapshot> {
             .collect(),
         settings: Some(settings),
         templates,
+        access: Some(access),
+        webhooks: Some(webhooks),
+        environments: Some(environments),
     })
 }
 
@@ -453,11 +535,7 @@ fn write_set(
     }
 
     if let Some(settings) = settings {
-        let contents = serialize_with_format(settings, format)?;
-        fs::write(
-            dir.join(format!("repo-settings.{}", format.ext())),
-            contents,
-        )?;
+        write_named_file(dir, "repo-settings", settings, format)?;
     }
 
     for tpl in templates {
@@ -471,6 +549,18 @@ fn write_set(
     Ok(())
 }
 
+fn write_named_file<T: Serialize>(
+    dir: &Path,
+    stem: &str,
+    value: &T,
+    format: OutputFormat,
+) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let contents = serialize_with_format(value, format)?;
+    fs::write(dir.join(format!("{stem}.{}", format.ext())), contents)?;
+    Ok(())
+}
+
 #[derive(Clone, Copy)]
 pub enum OutputFormat {
     Toml,
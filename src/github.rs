@@ -1,17 +1,26 @@
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use octocrab::Octocrab;
-use octocrab::models::{IssueState, Label, issues::Issue, pulls::PullRequest};
+use octocrab::models::pulls::{MergeableState, PullRequest};
+use octocrab::models::{IssueState, Label, issues::Issue};
 use octocrab::params;
 use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use tracing::warn;
 
+use crate::config::AutoMergeMethod;
 use crate::error::{Error, Result};
-use crate::sets::LabelSpec;
+use crate::sets::{LabelSpec, MilestoneSpec, MilestoneState};
 use crate::settings::{
-    BranchProtectionRule, BranchRestrictions, PullRequestSettings, RepoSettings,
-    RequiredPullRequestReviews, RequiredStatusChecks, ReviewDismissalRestrictions, StatusCheck,
+    AccessConfig, ActionsSettings, AllowedActions, AutolinkSpec, BranchProtectionRule,
+    BranchRestrictions, CustomProperties, DeploymentBranchPolicy, DiscussionCategorySpec,
+    DiscussionFormat, EnvironmentReviewers, EnvironmentSpec, ForkPrApprovalPolicy,
+    MergeCommitMessage, MergeCommitTitle, OrgBasePermission, OrgSettings, Permission,
+    PropertyValue, PullRequestSettings, RepoSettings, RequiredPullRequestReviews,
+    RequiredStatusChecks, ReviewDismissalRestrictions, SecuritySettings, SelectedActions,
+    SquashMergeCommitMessage, SquashMergeCommitTitle, StatusCheck, TeamPrivacy, TeamRole, TeamSpec,
+    WebhookContentType, WebhookSpec, WorkflowPermissions, merge_message_option_from_parts,
+    squash_option_from_parts,
 };
 
 #[derive(Debug, Clone)]
@@ -33,12 +42,97 @@ pub struct LabelUsageEntry {
     pub is_pr: bool,
 }
 
+/// An existing organization team; `spec.maintainers` and `spec.members` hold its current
+/// members by role.
+#[derive(Debug, Clone)]
+pub struct OrgTeam {
+    pub slug: String,
+    pub spec: TeamSpec,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepoWebhook {
+    pub id: u64,
+    pub spec: WebhookSpec,
+    /// GitHub masks the secret, so only its presence is known.
+    pub has_secret: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct PropertyValueResp {
+    property_name: String,
+    value: Option<PropertyValue>,
+}
+
+fn collect_property_values(values: Vec<PropertyValueResp>) -> CustomProperties {
+    values
+        .into_iter()
+        .filter_map(|p| p.value.map(|v| (p.property_name, v)))
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct RepoMilestone {
+    pub number: u64,
+    pub open_issues: u64,
+    pub spec: MilestoneSpec,
+}
+
+/// Discussions state of a repository; `discussions` counts the discussions in a category.
+#[derive(Debug, Clone, Default)]
+pub struct RepoDiscussions {
+    pub enabled: bool,
+    pub categories: Vec<RepoDiscussionCategory>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepoDiscussionCategory {
+    pub id: String,
+    pub discussions: u64,
+    pub spec: DiscussionCategorySpec,
+}
+
+#[derive(serde::Deserialize)]
+struct SlugResp {
+    slug: String,
+}
+
+/// Why an open governance PR no longer applies cleanly to its base branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Staleness {
+    Behind(u64),
+    Conflicting,
+}
+
+impl std::fmt::Display for Staleness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Staleness::Behind(1) => write!(f, "1 commit behind"),
+            Staleness::Behind(n) => write!(f, "{n} commits behind"),
+            Staleness::Conflicting => write!(f, "has merge conflicts"),
+        }
+    }
+}
+
+/// A file to write in a batched commit; `contents: None` deletes the path.
+#[derive(Debug, Clone)]
+pub struct FileChange {
+    pub path: String,
+    pub contents: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepoAutolink {
+    pub id: u64,
+    pub spec: AutolinkSpec,
+}
+
 impl GithubClient {
     pub fn new(token: &str, org: String) -> Result<Self> {
         let inner = Octocrab::builder()
             .personal_token(token.to_string())
             .build()
-            .map_err(Error::Octo)?;
+            .map_err(Error::from)?;
         Ok(Self { inner, org })
     }
 
@@ -80,12 +174,18 @@ impl GithubClient {
         Ok(())
     }
 
-    pub async fn update_label(&self, repo: &str, label: &LabelSpec) -> Result<()> {
+    /// Updates the label currently named `current_name`; a different `label.name` renames it.
+    pub async fn update_label(
+        &self,
+        repo: &str,
+        current_name: &str,
+        label: &LabelSpec,
+    ) -> Result<()> {
         let path = format!(
             "/repos/{}/{}/labels/{}",
             self.org,
             repo,
-            encode_label_name(&label.name)
+            encode_label_name(current_name)
         );
         #[derive(Serialize)]
         struct Body {
@@ -99,10 +199,8 @@ impl GithubClient {
             color: normalize_color(&label.color),
             description: label.description.clone(),
         };
-        self.inner
-            ._patch(path, Some(&body))
-            .await
-            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        let result = self.inner.patch(path, Some(&body)).await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
         Ok(())
     }
 
@@ -113,10 +211,8 @@ impl GithubClient {
             repo,
             encode_label_name(label_name)
         );
-        self.inner
-            ._delete(path, Option::<()>::None.as_ref())
-            .await
-            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        let result = self.inner.delete(path, Option::<()>::None.as_ref()).await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
         Ok(())
     }
 
@@ -167,23 +263,373 @@ impl GithubClient {
         }
     }
 
+    /// Current organization settings; fields the token cannot see (e.g. the two-factor
+    /// requirement for non-owners) are `None`. Default labels are never populated.
+    pub async fn get_org_settings(&self) -> Result<OrgSettings> {
+        #[derive(serde::Deserialize)]
+        struct OrgResp {
+            default_repository_permission: Option<OrgBasePermission>,
+            members_can_create_repositories: Option<bool>,
+            members_can_create_pages: Option<bool>,
+            members_can_fork_private_repositories: Option<bool>,
+            two_factor_requirement_enabled: Option<bool>,
+        }
+        let resp: OrgResp = self
+            .inner
+            .get(format!("/orgs/{}", self.org), None::<&()>)
+            .await?;
+        Ok(OrgSettings {
+            default_repository_permission: resp.default_repository_permission,
+            members_can_create_repositories: resp.members_can_create_repositories,
+            members_can_create_pages: resp.members_can_create_pages,
+            members_can_fork_private_repositories: resp.members_can_fork_private_repositories,
+            two_factor_requirement_enabled: resp.two_factor_requirement_enabled,
+            default_labels: Vec::new(),
+        })
+    }
+
+    /// Updates the member privileges declared in `settings`; report-only fields are ignored.
+    pub async fn update_org_settings(&self, settings: &OrgSettings) -> Result<()> {
+        #[derive(Serialize)]
+        struct OrgPatch {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            default_repository_permission: Option<OrgBasePermission>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            members_can_create_repositories: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            members_can_create_pages: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            members_can_fork_private_repositories: Option<bool>,
+        }
+        let body = OrgPatch {
+            default_repository_permission: settings.default_repository_permission,
+            members_can_create_repositories: settings.members_can_create_repositories,
+            members_can_create_pages: settings.members_can_create_pages,
+            members_can_fork_private_repositories: settings.members_can_fork_private_repositories,
+        };
+        let _: serde_json::Value = self
+            .inner
+            .patch(format!("/orgs/{}", self.org), Some(&body))
+            .await?;
+        Ok(())
+    }
+
     pub async fn get_repo_settings(&self, repo: &str) -> Result<RepoSettings> {
-        let repo_model = self.get_repo(repo).await?;
+        // Octocrab's Repository model lacks the merge/squash commit title and message fields,
+        // so read the raw repository JSON instead.
+        #[derive(serde::Deserialize)]
+        struct RepoSettingsResponse {
+            allow_merge_commit: Option<bool>,
+            allow_squash_merge: Option<bool>,
+            allow_rebase_merge: Option<bool>,
+            allow_auto_merge: Option<bool>,
+            delete_branch_on_merge: Option<bool>,
+            merge_commit_title: Option<MergeCommitTitle>,
+            merge_commit_message: Option<MergeCommitMessage>,
+            squash_merge_commit_title: Option<SquashMergeCommitTitle>,
+            squash_merge_commit_message: Option<SquashMergeCommitMessage>,
+        }
+
+        let resp: RepoSettingsResponse = self
+            .inner
+            .get(format!("/repos/{}/{}", self.org, repo), None::<&()>)
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
 
         Ok(RepoSettings {
             pull_requests: Some(PullRequestSettings {
-                allow_merge_commit: repo_model.allow_merge_commit,
-                allow_squash_merge: repo_model.allow_squash_merge,
-                allow_rebase_merge: repo_model.allow_rebase_merge,
-                allow_auto_merge: repo_model.allow_auto_merge,
-                delete_branch_on_merge: repo_model.delete_branch_on_merge,
-                merge_commit_message_option: None,
-                squash_merge_option: None,
+                allow_merge_commit: resp.allow_merge_commit,
+                allow_squash_merge: resp.allow_squash_merge,
+                allow_rebase_merge: resp.allow_rebase_merge,
+                allow_auto_merge: resp.allow_auto_merge,
+                delete_branch_on_merge: resp.delete_branch_on_merge,
+                merge_commit_message_option: merge_message_option_from_parts(
+                    resp.merge_commit_title.as_ref(),
+                    resp.merge_commit_message.as_ref(),
+                ),
+                squash_merge_option: squash_option_from_parts(
+                    resp.squash_merge_commit_title.as_ref(),
+                    resp.squash_merge_commit_message.as_ref(),
+                ),
             }),
             branch_protection: None,
+            actions: None,
+            security: None,
+        })
+    }
+
+    /// Reads the repository's Actions policy. Returns `None` when the token cannot read it.
+    pub async fn get_actions_settings(&self, repo: &str) -> Result<Option<ActionsSettings>> {
+        #[derive(serde::Deserialize)]
+        struct PermissionsResp {
+            enabled: Option<bool>,
+            allowed_actions: Option<AllowedActions>,
+        }
+        #[derive(serde::Deserialize)]
+        struct WorkflowResp {
+            default_workflow_permissions: Option<WorkflowPermissions>,
+            can_approve_pull_request_reviews: Option<bool>,
+        }
+        #[derive(serde::Deserialize)]
+        struct ForkApprovalResp {
+            approval_policy: Option<ForkPrApprovalPolicy>,
+        }
+
+        let base = format!("/repos/{}/{}/actions/permissions", self.org, repo);
+        let Some(permissions) = self
+            .get_optional::<PermissionsResp>(repo, &base, "actions permissions")
+            .await?
+        else {
+            return Ok(None);
+        };
+        let selected_actions = if permissions.allowed_actions == Some(AllowedActions::Selected) {
+            self.get_optional::<SelectedActions>(
+                repo,
+                &format!("{base}/selected-actions"),
+                "selected actions",
+            )
+            .await?
+        } else {
+            None
+        };
+        let workflow = self
+            .get_optional::<WorkflowResp>(repo, &format!("{base}/workflow"), "workflow permissions")
+            .await?;
+        // Only exposed for public repositories; private ones answer 404/422.
+        let fork_approval = self
+            .get_optional::<ForkApprovalResp>(
+                repo,
+                &format!("{base}/fork-pr-contributor-approval"),
+                "fork PR approval policy",
+            )
+            .await?;
+
+        Ok(Some(ActionsSettings {
+            enabled: permissions.enabled,
+            allowed_actions: permissions.allowed_actions,
+            selected_actions,
+            default_workflow_permissions: workflow
+                .as_ref()
+                .and_then(|w| w.default_workflow_permissions.clone()),
+            can_approve_pull_request_reviews: workflow
+                .as_ref()
+                .and_then(|w| w.can_approve_pull_request_reviews),
+            fork_pr_approval: fork_approval.and_then(|f| f.approval_policy),
+        }))
+    }
+
+    /// Reads security and analysis features; features the repository is not offered stay `None`.
+    pub async fn get_security_settings(&self, repo: &str) -> Result<SecuritySettings> {
+        #[derive(serde::Deserialize)]
+        struct StatusFlag {
+            status: String,
+        }
+        #[derive(serde::Deserialize)]
+        struct SecurityAndAnalysis {
+            advanced_security: Option<StatusFlag>,
+            secret_scanning: Option<StatusFlag>,
+            secret_scanning_push_protection: Option<StatusFlag>,
+            dependabot_security_updates: Option<StatusFlag>,
+        }
+        #[derive(serde::Deserialize)]
+        struct RepoResp {
+            security_and_analysis: Option<SecurityAndAnalysis>,
+        }
+        #[derive(serde::Deserialize)]
+        struct AutomatedFixesResp {
+            enabled: bool,
+        }
+
+        let route = format!("/repos/{}/{}", self.org, repo);
+        let resp: RepoResp = self
+            .inner
+            .get(&route, None::<&()>)
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        let enabled = |flag: Option<StatusFlag>| flag.map(|f| f.status == "enabled");
+        let sa = resp.security_and_analysis;
+
+        // This endpoint answers 204 when alerts are enabled and 404 when they are disabled.
+        let alerts = self
+            .inner
+            ._get(format!("{route}/vulnerability-alerts"))
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        let vulnerability_alerts = match alerts.status() {
+            reqwest::StatusCode::NO_CONTENT => Some(true),
+            reqwest::StatusCode::NOT_FOUND => Some(false),
+            _ => None,
+        };
+        let automated_security_fixes = self
+            .get_optional::<AutomatedFixesResp>(
+                repo,
+                &format!("{route}/automated-security-fixes"),
+                "automated security fixes",
+            )
+            .await?
+            .map(|r| r.enabled);
+
+        Ok(match sa {
+            Some(sa) => SecuritySettings {
+                advanced_security: enabled(sa.advanced_security),
+                secret_scanning: enabled(sa.secret_scanning),
+                secret_scanning_push_protection: enabled(sa.secret_scanning_push_protection),
+                dependabot_security_updates: enabled(sa.dependabot_security_updates),
+                vulnerability_alerts,
+                automated_security_fixes,
+            },
+            None => SecuritySettings {
+                vulnerability_alerts,
+                automated_security_fixes,
+                ..Default::default()
+            },
         })
     }
 
+    /// Applies the given security features one by one. Features rejected by GitHub (for
+    /// example because the organization's plan lacks them) are reported and skipped.
+    pub async fn update_security_settings(
+        &self,
+        repo: &str,
+        settings: &SecuritySettings,
+    ) -> Result<()> {
+        let route = format!("/repos/{}/{}", self.org, repo);
+        let status = |on: bool| if on { "enabled" } else { "disabled" };
+
+        let analysis = [
+            ("advanced_security", settings.advanced_security),
+            ("secret_scanning", settings.secret_scanning),
+            (
+                "secret_scanning_push_protection",
+                settings.secret_scanning_push_protection,
+            ),
+            (
+                "dependabot_security_updates",
+                settings.dependabot_security_updates,
+            ),
+        ];
+        for (feature, value) in analysis {
+            let Some(on) = value else { continue };
+            let body = serde_json::json!({
+                "security_and_analysis": { feature: { "status": status(on) } }
+            });
+            let resp = self
+                .inner
+                ._patch(&route, Some(&body))
+                .await
+                .map_err(|e| map_repo_error(&self.org, repo, e))?;
+            if !resp.status().is_success() {
+                warn!(
+                    "{} not applied for {}/{}: GitHub answered {}",
+                    feature,
+                    self.org,
+                    repo,
+                    resp.status()
+                );
+            }
+        }
+
+        let toggles = [
+            ("vulnerability-alerts", settings.vulnerability_alerts),
+            (
+                "automated-security-fixes",
+                settings.automated_security_fixes,
+            ),
+        ];
+        for (endpoint, value) in toggles {
+            let Some(on) = value else { continue };
+            let path = format!("{route}/{endpoint}");
+            let resp = if on {
+                self.inner._put(path, None::<&()>).await
+            } else {
+                self.inner._delete(path, None::<&()>).await
+            }
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+            if !resp.status().is_success() {
+                warn!(
+                    "{} not applied for {}/{}: GitHub answered {}",
+                    endpoint,
+                    self.org,
+                    repo,
+                    resp.status()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn update_actions_settings(
+        &self,
+        repo: &str,
+        settings: &ActionsSettings,
+    ) -> Result<()> {
+        #[derive(Serialize)]
+        struct PermissionsBody<'a> {
+            enabled: bool,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            allowed_actions: Option<&'a AllowedActions>,
+        }
+        #[derive(Serialize)]
+        struct WorkflowBody<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            default_workflow_permissions: Option<&'a WorkflowPermissions>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            can_approve_pull_request_reviews: Option<bool>,
+        }
+        #[derive(Serialize)]
+        struct ForkApprovalBody<'a> {
+            approval_policy: &'a ForkPrApprovalPolicy,
+        }
+
+        let base = format!("/repos/{}/{}/actions/permissions", self.org, repo);
+
+        if settings.enabled.is_some() || settings.allowed_actions.is_some() {
+            // `enabled` is mandatory for this endpoint; choosing allowed actions implies enabling.
+            let body = PermissionsBody {
+                enabled: settings.enabled.unwrap_or(true),
+                allowed_actions: settings.allowed_actions.as_ref(),
+            };
+            let result = self.inner.put(base.clone(), Some(&body)).await;
+            no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        }
+
+        if let Some(selected) = &settings.selected_actions {
+            let result = self
+                .inner
+                .put(format!("{base}/selected-actions"), Some(selected))
+                .await;
+            no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        }
+
+        if settings.default_workflow_permissions.is_some()
+            || settings.can_approve_pull_request_reviews.is_some()
+        {
+            let body = WorkflowBody {
+                default_workflow_permissions: settings.default_workflow_permissions.as_ref(),
+                can_approve_pull_request_reviews: settings.can_approve_pull_request_reviews,
+            };
+            let result = self
+                .inner
+                .put(format!("{base}/workflow"), Some(&body))
+                .await;
+            no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        }
+
+        if let Some(policy) = &settings.fork_pr_approval {
+            let body = ForkApprovalBody {
+                approval_policy: policy,
+            };
+            let result = self
+                .inner
+                .put(format!("{base}/fork-pr-contributor-approval"), Some(&body))
+                .await;
+            no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        }
+
+        Ok(())
+    }
+
     pub async fn update_repo_settings(&self, repo: &str, settings: &RepoSettings) -> Result<()> {
         #[derive(Serialize)]
         struct Body {
@@ -248,24 +694,956 @@ impl GithubClient {
             squash_merge_commit_title: option_title,
         };
 
-        // Only send an update if at least one field is present.
-        if serde_json::to_value(&body)
-            .ok()
-            .and_then(|v| v.as_object().cloned())
-            .map(|m| m.is_empty())
-            .unwrap_or(true)
-        {
-            return Ok(());
+        // Only send an update if at least one field is present.
+        if serde_json::to_value(&body)
+            .ok()
+            .and_then(|v| v.as_object().cloned())
+            .map(|m| m.is_empty())
+            .unwrap_or(true)
+        {
+            return Ok(());
+        }
+
+        let result = self
+            .inner
+            .patch(format!("/repos/{}/{}", self.org, repo), Some(&body))
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+
+        Ok(())
+    }
+
+    /// Reads the teams and outside collaborators with access to the repository.
+    pub async fn get_access(&self, repo: &str) -> Result<AccessConfig> {
+        #[derive(serde::Deserialize)]
+        struct TeamResp {
+            slug: String,
+            permission: String,
+        }
+        #[derive(serde::Deserialize)]
+        struct CollaboratorResp {
+            login: String,
+            role_name: String,
+        }
+        #[derive(serde::Deserialize)]
+        struct Invitee {
+            login: String,
+        }
+        #[derive(serde::Deserialize)]
+        struct InvitationResp {
+            invitee: Option<Invitee>,
+            permissions: String,
+        }
+
+        let mut access = AccessConfig::default();
+        let teams: Vec<TeamResp> = self
+            .get_all_pages(repo, &format!("/repos/{}/{}/teams", self.org, repo))
+            .await?;
+        for team in teams {
+            match Permission::from_api(&team.permission) {
+                Some(perm) => {
+                    access.teams.insert(team.slug, perm);
+                }
+                None => warn!(
+                    "ignoring team '{}' on {}/{} with custom role '{}'",
+                    team.slug, self.org, repo, team.permission
+                ),
+            }
+        }
+        let collaborators: Vec<CollaboratorResp> = self
+            .get_all_pages(
+                repo,
+                &format!(
+                    "/repos/{}/{}/collaborators?affiliation=outside",
+                    self.org, repo
+                ),
+            )
+            .await?;
+        for collaborator in collaborators {
+            match Permission::from_api(&collaborator.role_name) {
+                Some(perm) => {
+                    access.collaborators.insert(collaborator.login, perm);
+                }
+                None => warn!(
+                    "ignoring collaborator '{}' on {}/{} with custom role '{}'",
+                    collaborator.login, self.org, repo, collaborator.role_name
+                ),
+            }
+        }
+        // Invited users count as collaborators so that apply does not invite them again.
+        let invitations: Vec<InvitationResp> = self
+            .get_all_pages(repo, &format!("/repos/{}/{}/invitations", self.org, repo))
+            .await?;
+        for invitation in invitations {
+            let Some(invitee) = invitation.invitee else {
+                continue;
+            };
+            if let Some(perm) = Permission::from_api(&invitation.permissions) {
+                access.collaborators.entry(invitee.login).or_insert(perm);
+            }
+        }
+        Ok(access)
+    }
+
+    pub async fn set_team_permission(
+        &self,
+        repo: &str,
+        team_slug: &str,
+        permission: Permission,
+    ) -> Result<()> {
+        let path = format!(
+            "/orgs/{}/teams/{}/repos/{}/{}",
+            self.org, team_slug, self.org, repo
+        );
+        let body = serde_json::json!({ "permission": permission.as_str() });
+        let result = self.inner.put(path, Some(&body)).await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    pub async fn remove_team_access(&self, repo: &str, team_slug: &str) -> Result<()> {
+        let path = format!(
+            "/orgs/{}/teams/{}/repos/{}/{}",
+            self.org, team_slug, self.org, repo
+        );
+        let result = self.inner.delete(path, None::<&()>).await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    /// Adds or updates an outside collaborator; new collaborators receive an invitation.
+    pub async fn set_collaborator_permission(
+        &self,
+        repo: &str,
+        login: &str,
+        permission: Permission,
+    ) -> Result<()> {
+        let path = format!("/repos/{}/{}/collaborators/{}", self.org, repo, login);
+        let body = serde_json::json!({ "permission": permission.as_str() });
+        let result = self.inner.put(path, Some(&body)).await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    /// Removes an outside collaborator; GitHub also cancels a pending invitation.
+    pub async fn remove_collaborator(&self, repo: &str, login: &str) -> Result<()> {
+        let path = format!("/repos/{}/{}/collaborators/{}", self.org, repo, login);
+        let result = self.inner.delete(path, None::<&()>).await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    pub async fn list_webhooks(&self, repo: &str) -> Result<Vec<RepoWebhook>> {
+        #[derive(serde::Deserialize)]
+        struct HookConfig {
+            url: Option<String>,
+            content_type: Option<WebhookContentType>,
+            // GitHub returns "0"/"1" here, occasionally as a number.
+            insecure_ssl: Option<serde_json::Value>,
+            secret: Option<String>,
+        }
+        #[derive(serde::Deserialize)]
+        struct HookResp {
+            id: u64,
+            name: String,
+            active: bool,
+            events: Vec<String>,
+            config: HookConfig,
+        }
+
+        let hooks: Vec<HookResp> = self
+            .get_all_pages(repo, &format!("/repos/{}/{}/hooks", self.org, repo))
+            .await?;
+        Ok(hooks
+            .into_iter()
+            .filter(|h| h.name == "web")
+            .filter_map(|h| {
+                let url = h.config.url?;
+                let insecure_ssl = h.config.insecure_ssl.map(|v| match v {
+                    serde_json::Value::String(s) => s == "1",
+                    serde_json::Value::Number(n) => n.as_u64() == Some(1),
+                    _ => false,
+                });
+                Some(RepoWebhook {
+                    id: h.id,
+                    spec: WebhookSpec {
+                        url,
+                        content_type: h.config.content_type,
+                        events: Some(h.events),
+                        active: Some(h.active),
+                        insecure_ssl,
+                        secret_env: None,
+                    },
+                    has_secret: h.config.secret.is_some(),
+                })
+            })
+            .collect())
+    }
+
+    pub async fn create_webhook(
+        &self,
+        repo: &str,
+        hook: &WebhookSpec,
+        secret: Option<&str>,
+    ) -> Result<()> {
+        let mut body = webhook_body(hook);
+        body["config"] = webhook_config(hook, secret);
+        body["name"] = serde_json::json!("web");
+        let result = self
+            .inner
+            .post(format!("/repos/{}/{}/hooks", self.org, repo), Some(&body))
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    /// Updates events and the active flag. GitHub replaces the whole `config` object on
+    /// update, dropping the secret unless it is sent again, so `config` is only sent when
+    /// `send_config` is set.
+    pub async fn update_webhook(
+        &self,
+        repo: &str,
+        id: u64,
+        hook: &WebhookSpec,
+        send_config: bool,
+        secret: Option<&str>,
+    ) -> Result<()> {
+        let mut body = webhook_body(hook);
+        if send_config {
+            body["config"] = webhook_config(hook, secret);
+        }
+        let result = self
+            .inner
+            .patch(
+                format!("/repos/{}/{}/hooks/{}", self.org, repo, id),
+                Some(&body),
+            )
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    pub async fn delete_webhook(&self, repo: &str, id: u64) -> Result<()> {
+        let result = self
+            .inner
+            .delete(
+                format!("/repos/{}/{}/hooks/{}", self.org, repo, id),
+                None::<&()>,
+            )
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    pub async fn get_property_values(&self, repo: &str) -> Result<CustomProperties> {
+        let values: Vec<PropertyValueResp> = self
+            .inner
+            .get(
+                format!("/repos/{}/{}/properties/values", self.org, repo),
+                None::<&()>,
+            )
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        Ok(collect_property_values(values))
+    }
+
+    /// Sets all given custom property values in a single request.
+    pub async fn update_property_values(
+        &self,
+        repo: &str,
+        values: &[(&str, &PropertyValue)],
+    ) -> Result<()> {
+        let properties: Vec<_> = values
+            .iter()
+            .map(|(name, value)| serde_json::json!({ "property_name": name, "value": value }))
+            .collect();
+        match self
+            .inner
+            .patch::<serde_json::Value, _, _>(
+                format!("/repos/{}/{}/properties/values", self.org, repo),
+                Some(&serde_json::json!({ "properties": properties })),
+            )
+            .await
+        {
+            // Success is a 204 without a body, which cannot be parsed as JSON.
+            Ok(_) | Err(octocrab::Error::Json { .. }) => Ok(()),
+            Err(e) => Err(map_repo_error(&self.org, repo, e)),
+        }
+    }
+
+    /// Custom property values for every repository in the organization.
+    pub async fn list_org_property_values(&self) -> Result<Vec<(String, CustomProperties)>> {
+        #[derive(serde::Deserialize)]
+        struct RepoValues {
+            repository_name: String,
+            properties: Vec<PropertyValueResp>,
+        }
+        let repos: Vec<RepoValues> = self
+            .get_all_org_pages(&format!("/orgs/{}/properties/values", self.org))
+            .await?;
+        Ok(repos
+            .into_iter()
+            .map(|r| (r.repository_name, collect_property_values(r.properties)))
+            .collect())
+    }
+
+    pub async fn get_discussions(&self, repo: &str) -> Result<RepoDiscussions> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct CategoryNode {
+            id: String,
+            name: String,
+            emoji: Option<String>,
+            description: Option<String>,
+            is_answerable: bool,
+        }
+        const QUERY: &str = "query($owner: String!, $name: String!) {
+            repository(owner: $owner, name: $name) {
+                hasDiscussionsEnabled
+                discussionCategories(first: 100) {
+                    nodes { id name emoji description isAnswerable }
+                }
+            }
+        }";
+        let data = self
+            .graphql_query(repo, QUERY, serde_json::json!({}))
+            .await?;
+        let repository = &data["repository"];
+        let nodes: Vec<CategoryNode> =
+            serde_json::from_value(repository["discussionCategories"]["nodes"].clone())?;
+
+        // One aliased `discussions` field per category, so all counts come back in one query.
+        let counts = if nodes.is_empty() {
+            serde_json::Value::Null
+        } else {
+            let mut params = String::new();
+            let mut fields = String::new();
+            let mut variables = serde_json::json!({});
+            for (i, node) in nodes.iter().enumerate() {
+                params.push_str(&format!(", $c{i}: ID!"));
+                fields.push_str(&format!(
+                    " c{i}: discussions(first: 1, categoryId: $c{i}) {{ totalCount }}"
+                ));
+                variables[format!("c{i}")] = serde_json::json!(node.id);
+            }
+            let query = format!(
+                "query($owner: String!, $name: String!{params}) {{
+                    repository(owner: $owner, name: $name) {{{fields} }}
+                }}"
+            );
+            self.graphql_query(repo, &query, variables).await?["repository"].take()
+        };
+
+        let categories = nodes
+            .into_iter()
+            .enumerate()
+            .map(|(i, node)| RepoDiscussionCategory {
+                discussions: counts[format!("c{i}")]["totalCount"]
+                    .as_u64()
+                    .unwrap_or_default(),
+                spec: DiscussionCategorySpec {
+                    name: node.name,
+                    emoji: node.emoji,
+                    description: node.description.filter(|d| !d.is_empty()),
+                    // Only answerability is exposed; other formats are indistinguishable.
+                    format: node.is_answerable.then_some(DiscussionFormat::Question),
+                },
+                id: node.id,
+            })
+            .collect();
+        Ok(RepoDiscussions {
+            enabled: repository["hasDiscussionsEnabled"]
+                .as_bool()
+                .unwrap_or_default(),
+            categories,
+        })
+    }
+
+    pub async fn set_discussions_enabled(&self, repo: &str, enabled: bool) -> Result<()> {
+        let result = self
+            .inner
+            .patch(
+                format!("/repos/{}/{}", self.org, repo),
+                Some(&serde_json::json!({ "has_discussions": enabled })),
+            )
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    /// Runs a GraphQL query scoped to `repo` (`$owner` and `$name` are provided) and
+    /// returns its `data`.
+    async fn graphql_query(
+        &self,
+        repo: &str,
+        query: &str,
+        mut variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        variables["owner"] = serde_json::json!(self.org);
+        variables["name"] = serde_json::json!(repo);
+        let mut resp: serde_json::Value = self
+            .inner
+            .graphql(&serde_json::json!({ "query": query, "variables": variables }))
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        if let Some(errors) = resp.get("errors").and_then(|e| e.as_array())
+            && !errors.is_empty()
+        {
+            let messages: Vec<&str> = errors
+                .iter()
+                .filter_map(|e| e["message"].as_str())
+                .collect();
+            return Err(Error::GraphQl(messages.join("; ")));
+        }
+        Ok(resp["data"].take())
+    }
+
+    pub async fn list_milestones(&self, repo: &str) -> Result<Vec<RepoMilestone>> {
+        #[derive(serde::Deserialize)]
+        struct MilestoneResp {
+            number: u64,
+            title: String,
+            description: Option<String>,
+            due_on: Option<String>,
+            state: Option<MilestoneState>,
+            open_issues: u64,
+        }
+        let milestones: Vec<MilestoneResp> = self
+            .get_all_pages(
+                repo,
+                &format!("/repos/{}/{}/milestones?state=all", self.org, repo),
+            )
+            .await?;
+        Ok(milestones
+            .into_iter()
+            .map(|m| RepoMilestone {
+                number: m.number,
+                open_issues: m.open_issues,
+                spec: MilestoneSpec {
+                    title: m.title,
+                    description: m.description.filter(|d| !d.is_empty()),
+                    // Only the date matters; GitHub stores a timestamp.
+                    due_on: m.due_on.map(|d| d.chars().take(10).collect()),
+                    state: m.state,
+                },
+            })
+            .collect())
+    }
+
+    pub async fn create_milestone(&self, repo: &str, milestone: &MilestoneSpec) -> Result<()> {
+        let result = self
+            .inner
+            .post(
+                format!("/repos/{}/{}/milestones", self.org, repo),
+                Some(&milestone_body(milestone)),
+            )
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    pub async fn update_milestone(
+        &self,
+        repo: &str,
+        number: u64,
+        milestone: &MilestoneSpec,
+    ) -> Result<()> {
+        let result = self
+            .inner
+            .patch(
+                format!("/repos/{}/{}/milestones/{}", self.org, repo, number),
+                Some(&milestone_body(milestone)),
+            )
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    pub async fn delete_milestone(&self, repo: &str, number: u64) -> Result<()> {
+        let result = self
+            .inner
+            .delete(
+                format!("/repos/{}/{}/milestones/{}", self.org, repo, number),
+                None::<&()>,
+            )
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    pub async fn list_autolinks(&self, repo: &str) -> Result<Vec<RepoAutolink>> {
+        #[derive(serde::Deserialize)]
+        struct AutolinkResp {
+            id: u64,
+            key_prefix: String,
+            url_template: String,
+            is_alphanumeric: Option<bool>,
+        }
+        let links: Vec<AutolinkResp> = self
+            .inner
+            .get(
+                format!("/repos/{}/{}/autolinks", self.org, repo),
+                None::<&()>,
+            )
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        Ok(links
+            .into_iter()
+            .map(|l| RepoAutolink {
+                id: l.id,
+                spec: AutolinkSpec {
+                    key_prefix: l.key_prefix,
+                    url_template: l.url_template,
+                    is_alphanumeric: l.is_alphanumeric,
+                },
+            })
+            .collect())
+    }
+
+    pub async fn create_autolink(&self, repo: &str, link: &AutolinkSpec) -> Result<()> {
+        let body = serde_json::json!({
+            "key_prefix": link.key_prefix,
+            "url_template": link.url_template,
+            "is_alphanumeric": link.is_alphanumeric.unwrap_or(true),
+        });
+        let result = self
+            .inner
+            .post(
+                format!("/repos/{}/{}/autolinks", self.org, repo),
+                Some(&body),
+            )
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    pub async fn delete_autolink(&self, repo: &str, id: u64) -> Result<()> {
+        let result = self
+            .inner
+            .delete(
+                format!("/repos/{}/{}/autolinks/{}", self.org, repo, id),
+                None::<&()>,
+            )
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    pub async fn list_environments(&self, repo: &str) -> Result<Vec<EnvironmentSpec>> {
+        #[derive(serde::Deserialize)]
+        struct ReviewerResp {
+            #[serde(rename = "type")]
+            kind: String,
+            reviewer: SimpleActor,
+        }
+        #[derive(serde::Deserialize)]
+        struct ProtectionRuleResp {
+            #[serde(rename = "type")]
+            kind: String,
+            wait_timer: Option<u32>,
+            prevent_self_review: Option<bool>,
+            reviewers: Option<Vec<ReviewerResp>>,
+        }
+        #[derive(serde::Deserialize)]
+        struct BranchPolicyResp {
+            protected_branches: bool,
+            custom_branch_policies: bool,
+        }
+        #[derive(serde::Deserialize)]
+        struct EnvironmentResp {
+            name: String,
+            can_admins_bypass: Option<bool>,
+            #[serde(default)]
+            protection_rules: Vec<ProtectionRuleResp>,
+            deployment_branch_policy: Option<BranchPolicyResp>,
+        }
+        #[derive(serde::Deserialize)]
+        struct EnvironmentsResp {
+            #[serde(default)]
+            environments: Vec<EnvironmentResp>,
+        }
+
+        const PER_PAGE: usize = 100;
+        let mut environments = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let path = format!(
+                "/repos/{}/{}/environments?per_page={PER_PAGE}&page={page}",
+                self.org, repo
+            );
+            let Some(resp) = self
+                .get_optional::<EnvironmentsResp>(repo, &path, "environments")
+                .await?
+            else {
+                break;
+            };
+            let done = resp.environments.len() < PER_PAGE;
+            environments.extend(resp.environments);
+            if done {
+                break;
+            }
+            page += 1;
+        }
+
+        let mut envs = Vec::new();
+        for env in environments {
+            let mut spec = EnvironmentSpec {
+                name: env.name,
+                can_admins_bypass: env.can_admins_bypass,
+                ..Default::default()
+            };
+            for rule in env.protection_rules {
+                match rule.kind.as_str() {
+                    "wait_timer" => spec.wait_timer = rule.wait_timer,
+                    "required_reviewers" => {
+                        spec.prevent_self_review = rule.prevent_self_review;
+                        let mut reviewers = EnvironmentReviewers::default();
+                        for r in rule.reviewers.unwrap_or_default() {
+                            match (r.kind.as_str(), r.reviewer) {
+                                ("Team", actor) => reviewers.teams.extend(actor.slug),
+                                (_, actor) => reviewers.users.extend(actor.login),
+                            }
+                        }
+                        spec.reviewers = Some(reviewers);
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(policy) = env.deployment_branch_policy {
+                let custom_branches = if policy.custom_branch_policies {
+                    self.list_deployment_branch_policies(repo, &spec.name)
+                        .await?
+                        .into_iter()
+                        .map(|(_, name)| name)
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                spec.deployment_branch_policy = Some(DeploymentBranchPolicy {
+                    protected_branches: policy.protected_branches,
+                    custom_branches,
+                });
+            }
+            envs.push(spec);
+        }
+        Ok(envs)
+    }
+
+    /// Creates or updates an environment, then syncs its custom branch policies.
+    pub async fn upsert_environment(&self, repo: &str, env: &EnvironmentSpec) -> Result<()> {
+        #[derive(Serialize)]
+        struct ReviewerReq {
+            #[serde(rename = "type")]
+            kind: &'static str,
+            id: u64,
+        }
+        #[derive(Serialize)]
+        struct BranchPolicyReq {
+            protected_branches: bool,
+            custom_branch_policies: bool,
+        }
+        #[derive(Serialize)]
+        struct Body {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            wait_timer: Option<u32>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            prevent_self_review: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            reviewers: Option<Vec<ReviewerReq>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            can_admins_bypass: Option<bool>,
+            /// `Some(None)` is sent as `null`, which allows deployments from any branch.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            deployment_branch_policy: Option<Option<BranchPolicyReq>>,
+        }
+
+        let reviewers = match &env.reviewers {
+            Some(r) => {
+                let mut list = Vec::new();
+                for login in &r.users {
+                    list.push(ReviewerReq {
+                        kind: "User",
+                        id: self
+                            .user_id(login)
+                            .await
+                            .map_err(|e| map_repo_error(&self.org, repo, e))?,
+                    });
+                }
+                for slug in &r.teams {
+                    list.push(ReviewerReq {
+                        kind: "Team",
+                        id: self
+                            .team_id(slug)
+                            .await
+                            .map_err(|e| map_repo_error(&self.org, repo, e))?,
+                    });
+                }
+                Some(list)
+            }
+            None => None,
+        };
+        let body = Body {
+            wait_timer: env.wait_timer,
+            prevent_self_review: env.prevent_self_review,
+            reviewers,
+            can_admins_bypass: env.can_admins_bypass,
+            deployment_branch_policy: env.deployment_branch_policy.as_ref().map(|p| {
+                (!p.allows_any_branch()).then_some(BranchPolicyReq {
+                    protected_branches: p.protected_branches,
+                    custom_branch_policies: !p.custom_branches.is_empty(),
+                })
+            }),
+        };
+        let base = format!(
+            "/repos/{}/{}/environments/{}",
+            self.org,
+            repo,
+            utf8_percent_encode(&env.name, NON_ALPHANUMERIC)
+        );
+        let result = self.inner.put(base.clone(), Some(&body)).await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+
+        if let Some(policy) = &env.deployment_branch_policy
+            && !policy.custom_branches.is_empty()
+        {
+            let existing = self
+                .list_deployment_branch_policies(repo, &env.name)
+                .await?;
+            for pattern in &policy.custom_branches {
+                if !existing.iter().any(|(_, name)| name == pattern) {
+                    let body = serde_json::json!({ "name": pattern, "type": "branch" });
+                    let result = self
+                        .inner
+                        .post(format!("{base}/deployment-branch-policies"), Some(&body))
+                        .await;
+                    no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+                }
+            }
+            for (id, name) in &existing {
+                if !policy.custom_branches.contains(name) {
+                    let result = self
+                        .inner
+                        .delete(
+                            format!("{base}/deployment-branch-policies/{id}"),
+                            None::<&()>,
+                        )
+                        .await;
+                    no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns (policy id, branch pattern) pairs.
+    async fn list_deployment_branch_policies(
+        &self,
+        repo: &str,
+        environment: &str,
+    ) -> Result<Vec<(u64, String)>> {
+        #[derive(serde::Deserialize)]
+        struct PolicyResp {
+            id: u64,
+            name: String,
+        }
+        #[derive(serde::Deserialize)]
+        struct PoliciesResp {
+            branch_policies: Vec<PolicyResp>,
+        }
+        const PER_PAGE: usize = 100;
+        let mut policies = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let path = format!(
+                "/repos/{}/{}/environments/{}/deployment-branch-policies?per_page={PER_PAGE}&page={page}",
+                self.org,
+                repo,
+                utf8_percent_encode(environment, NON_ALPHANUMERIC)
+            );
+            let resp: PoliciesResp = self
+                .inner
+                .get(path, None::<&()>)
+                .await
+                .map_err(|e| map_repo_error(&self.org, repo, e))?;
+            let done = resp.branch_policies.len() < PER_PAGE;
+            policies.extend(resp.branch_policies);
+            if done {
+                break;
+            }
+            page += 1;
+        }
+        Ok(policies.into_iter().map(|p| (p.id, p.name)).collect())
+    }
+
+    pub async fn list_teams(&self) -> Result<Vec<OrgTeam>> {
+        #[derive(serde::Deserialize)]
+        struct ParentResp {
+            name: String,
+        }
+        #[derive(serde::Deserialize)]
+        struct TeamResp {
+            name: String,
+            slug: String,
+            description: Option<String>,
+            privacy: Option<TeamPrivacy>,
+            parent: Option<ParentResp>,
+        }
+        #[derive(serde::Deserialize)]
+        struct MemberResp {
+            login: String,
+        }
+        let teams: Vec<TeamResp> = self
+            .get_all_org_pages(&format!("/orgs/{}/teams", self.org))
+            .await?;
+        let mut out = Vec::with_capacity(teams.len());
+        for team in teams {
+            let mut by_role = Vec::new();
+            for role in ["maintainer", "member"] {
+                let members: Vec<MemberResp> = self
+                    .get_all_org_pages(&format!(
+                        "/orgs/{}/teams/{}/members?role={role}",
+                        self.org, team.slug
+                    ))
+                    .await?;
+                by_role.push(members.into_iter().map(|m| m.login).collect::<Vec<_>>());
+            }
+            let members = by_role.pop().unwrap_or_default();
+            let maintainers = by_role.pop().unwrap_or_default();
+            out.push(OrgTeam {
+                slug: team.slug,
+                spec: TeamSpec {
+                    name: team.name,
+                    renamed_from: None,
+                    description: team.description.filter(|d| !d.is_empty()),
+                    privacy: team.privacy,
+                    parent: team.parent.map(|p| p.name),
+                    maintainers,
+                    members,
+                },
+            });
+        }
+        Ok(out)
+    }
+
+    /// Creates a team and returns its slug. GitHub makes the authenticated user a maintainer
+    /// of every team it creates; that membership is removed again unless `spec` lists the user.
+    pub async fn create_team(&self, spec: &TeamSpec, parent_slug: Option<&str>) -> Result<String> {
+        #[derive(serde::Deserialize)]
+        struct UserResp {
+            login: String,
+        }
+        let mut body = serde_json::json!({ "name": spec.name });
+        self.fill_team_body(&mut body, spec, parent_slug).await?;
+        let resp: SlugResp = self
+            .inner
+            .post(format!("/orgs/{}/teams", self.org), Some(&body))
+            .await?;
+        let creator: UserResp = self.inner.get("/user", None::<&()>).await?;
+        let declared = spec
+            .maintainers
+            .iter()
+            .chain(&spec.members)
+            .any(|u| u.eq_ignore_ascii_case(&creator.login));
+        if !declared {
+            self.remove_team_membership(&resp.slug, &creator.login)
+                .await?;
+        }
+        Ok(resp.slug)
+    }
+
+    /// Updates (and possibly renames) the team at `slug`, returning its new slug.
+    pub async fn update_team(
+        &self,
+        slug: &str,
+        spec: &TeamSpec,
+        parent_slug: Option<&str>,
+    ) -> Result<String> {
+        let mut body = serde_json::json!({ "name": spec.name });
+        self.fill_team_body(&mut body, spec, parent_slug).await?;
+        if parent_slug.is_none() {
+            body["parent_team_id"] = serde_json::Value::Null;
+        }
+        let resp: SlugResp = self
+            .inner
+            .patch(format!("/orgs/{}/teams/{}", self.org, slug), Some(&body))
+            .await?;
+        Ok(resp.slug)
+    }
+
+    async fn fill_team_body(
+        &self,
+        body: &mut serde_json::Value,
+        spec: &TeamSpec,
+        parent_slug: Option<&str>,
+    ) -> Result<()> {
+        if let Some(description) = &spec.description {
+            body["description"] = serde_json::json!(description);
         }
+        if let Some(privacy) = spec.privacy {
+            body["privacy"] = serde_json::json!(privacy);
+        }
+        if let Some(parent) = parent_slug {
+            body["parent_team_id"] = serde_json::json!(self.team_id(parent).await?);
+        }
+        Ok(())
+    }
 
-        self.inner
-            ._patch(format!("/repos/{}/{}", self.org, repo), Some(&body))
-            .await
-            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+    pub async fn delete_team(&self, slug: &str) -> Result<()> {
+        let result = self
+            .inner
+            .delete(format!("/orgs/{}/teams/{}", self.org, slug), None::<&()>)
+            .await;
+        no_content(result, Error::from)?;
+        Ok(())
+    }
+
+    pub async fn set_team_membership(&self, slug: &str, user: &str, role: TeamRole) -> Result<()> {
+        let _: serde_json::Value = self
+            .inner
+            .put(
+                format!("/orgs/{}/teams/{}/memberships/{}", self.org, slug, user),
+                Some(&serde_json::json!({ "role": role })),
+            )
+            .await?;
+        Ok(())
+    }
 
+    pub async fn remove_team_membership(&self, slug: &str, user: &str) -> Result<()> {
+        let result = self
+            .inner
+            .delete(
+                format!("/orgs/{}/teams/{}/memberships/{}", self.org, slug, user),
+                None::<&()>,
+            )
+            .await;
+        no_content(result, Error::from)?;
         Ok(())
     }
 
+    async fn user_id(&self, login: &str) -> std::result::Result<u64, octocrab::Error> {
+        #[derive(serde::Deserialize)]
+        struct UserResp {
+            id: u64,
+        }
+        let resp: UserResp = self
+            .inner
+            .get(format!("/users/{login}"), None::<&()>)
+            .await?;
+        Ok(resp.id)
+    }
+
+    async fn team_id(&self, slug: &str) -> std::result::Result<u64, octocrab::Error> {
+        #[derive(serde::Deserialize)]
+        struct TeamResp {
+            id: u64,
+        }
+        let resp: TeamResp = self
+            .inner
+            .get(format!("/orgs/{}/teams/{}", self.org, slug), None::<&()>)
+            .await?;
+        Ok(resp.id)
+    }
+
     pub async fn get_file(
         &self,
         repo: &str,
@@ -313,65 +1691,122 @@ impl GithubClient {
         }
     }
 
-    pub async fn put_file(
+    /// Writes all `changes` to `branch` as a single commit built through the Git Data API
+    /// (tree with inline blob contents, commit, ref update). Returns the new commit sha.
+    pub async fn commit_files(
         &self,
         repo: &str,
-        path: &str,
-        content: &str,
-        sha: Option<String>,
+        branch: &str,
+        changes: &[FileChange],
         message: &str,
-        branch: Option<&str>,
-    ) -> Result<()> {
-        #[derive(Serialize)]
-        struct Body<'a> {
-            message: &'a str,
-            content: String,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            sha: Option<String>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            branch: Option<&'a str>,
-        }
+    ) -> Result<String> {
+        let head = self.get_branch_sha(repo, branch).await?;
+        self.commit_onto(repo, branch, &head, changes, message, false)
+            .await
+    }
 
-        let body = Body {
-            message,
-            content: BASE64.encode(content.as_bytes()),
-            sha,
-            branch,
-        };
-        let route = format!("/repos/{}/{}/contents/{}", self.org, repo, path);
-        self.inner
-            ._put(route, Some(&body))
+    /// Replaces the history of `branch` with a single commit of `changes` on top of `base_sha`.
+    /// The ref is force-moved once, so the branch never points at the bare base in between.
+    pub async fn rebuild_branch(
+        &self,
+        repo: &str,
+        branch: &str,
+        base_sha: &str,
+        changes: &[FileChange],
+        message: &str,
+    ) -> Result<String> {
+        self.commit_onto(repo, branch, base_sha, changes, message, true)
             .await
-            .map_err(|e| map_repo_error(&self.org, repo, e))?;
-        Ok(())
     }
 
-    pub async fn delete_file(
+    async fn commit_onto(
         &self,
         repo: &str,
-        path: &str,
-        sha: &str,
+        branch: &str,
+        parent: &str,
+        changes: &[FileChange],
         message: &str,
-        branch: Option<&str>,
-    ) -> Result<()> {
-        #[derive(Serialize)]
-        struct Body<'a> {
-            message: &'a str,
-            sha: &'a str,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            branch: Option<&'a str>,
+        force: bool,
+    ) -> Result<String> {
+        #[derive(serde::Deserialize)]
+        struct ShaResp {
+            sha: String,
         }
-        let body = Body {
-            message,
-            sha,
-            branch,
-        };
-        let route = format!("/repos/{}/{}/contents/{}", self.org, repo, path);
-        self.inner
-            ._delete(route, Some(&body))
+        #[derive(serde::Deserialize)]
+        struct CommitResp {
+            tree: ShaResp,
+        }
+
+        let base: CommitResp = self
+            .inner
+            .get(
+                format!("/repos/{}/{}/git/commits/{}", self.org, repo, parent),
+                None::<&()>,
+            )
             .await
             .map_err(|e| map_repo_error(&self.org, repo, e))?;
-        Ok(())
+
+        let entries: Vec<serde_json::Value> = changes
+            .iter()
+            .map(|change| match &change.contents {
+                Some(contents) => serde_json::json!({
+                    "path": change.path,
+                    "mode": "100644",
+                    "type": "blob",
+                    "content": contents,
+                }),
+                // A null sha removes the path from the base tree.
+                None => serde_json::json!({
+                    "path": change.path,
+                    "mode": "100644",
+                    "type": "blob",
+                    "sha": null,
+                }),
+            })
+            .collect();
+        let tree: ShaResp = self
+            .inner
+            .post(
+                format!("/repos/{}/{}/git/trees", self.org, repo),
+                Some(&serde_json::json!({ "base_tree": base.tree.sha, "tree": entries })),
+            )
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        let commit: ShaResp = self
+            .inner
+            .post(
+                format!("/repos/{}/{}/git/commits", self.org, repo),
+                Some(&serde_json::json!({
+                    "message": message,
+                    "tree": tree.sha,
+                    "parents": [parent],
+                })),
+            )
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        let _: serde_json::Value = self
+            .inner
+            .patch(
+                format!("/repos/{}/{}/git/refs/heads/{}", self.org, repo, branch),
+                Some(&serde_json::json!({ "sha": commit.sha, "force": force })),
+            )
+            .await
+            .map_err(|e| match &e {
+                // Branch protection and rulesets reject the ref update with 403/409/422.
+                octocrab::Error::GitHub { source, .. }
+                    if matches!(source.status_code.as_u16(), 403 | 409 | 422)
+                        && (source.message.to_lowercase().contains("protected")
+                            || source.message.to_lowercase().contains("rule")) =>
+                {
+                    Error::ProtectedBranch {
+                        repo: repo.to_string(),
+                        branch: branch.to_string(),
+                        reason: source.message.clone(),
+                    }
+                }
+                _ => map_repo_error(&self.org, repo, e),
+            })?;
+        Ok(commit.sha)
     }
 
     pub async fn list_github_files(
@@ -475,6 +1910,21 @@ impl GithubClient {
         Ok(branches)
     }
 
+    pub async fn delete_branch_protection(&self, repo: &str, pattern: &str) -> Result<()> {
+        let result = self
+            .inner
+            .delete(
+                format!(
+                    "/repos/{}/{}/branches/{}/protection",
+                    self.org, repo, pattern
+                ),
+                None::<&()>,
+            )
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
     pub async fn set_branch_protection(
         &self,
         repo: &str,
@@ -485,7 +1935,11 @@ impl GithubClient {
             self.org, repo, rule.pattern
         );
         let body = BranchProtectionRequest::from_rule(rule);
-        match self.inner._put(path, Some(&body)).await {
+        match self
+            .inner
+            .put::<serde_json::Value, _, _>(path, Some(&body))
+            .await
+        {
             Ok(_) => Ok(()),
             Err(octocrab::Error::GitHub { ref source, .. })
                 if source.status_code == reqwest::StatusCode::FORBIDDEN =>
@@ -519,6 +1973,108 @@ impl GithubClient {
         Ok(resp.object.sha)
     }
 
+    /// Messages of the commits `head` has on top of `base`, oldest first.
+    pub async fn branch_commit_messages(
+        &self,
+        repo: &str,
+        base: &str,
+        head: &str,
+    ) -> Result<Vec<String>> {
+        #[derive(serde::Deserialize)]
+        struct CommitDetails {
+            message: String,
+        }
+        #[derive(serde::Deserialize)]
+        struct CompareCommit {
+            commit: CommitDetails,
+        }
+        #[derive(serde::Deserialize)]
+        struct CompareResp {
+            commits: Vec<CompareCommit>,
+        }
+        let compare: CompareResp = self
+            .inner
+            .get(
+                format!("/repos/{}/{}/compare/{}...{}", self.org, repo, base, head),
+                None::<&()>,
+            )
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        Ok(compare
+            .commits
+            .into_iter()
+            .map(|c| c.commit.message)
+            .collect())
+    }
+
+    /// Checks whether an open PR conflicts with or lags behind `base`.
+    pub async fn pr_staleness(
+        &self,
+        repo: &str,
+        number: u64,
+        base: &str,
+        head: &str,
+    ) -> Result<Option<Staleness>> {
+        #[derive(serde::Deserialize)]
+        struct CompareResp {
+            behind_by: u64,
+        }
+        // `mergeable` is only computed on the single-PR endpoint and may still be pending.
+        let pr: PullRequest = self
+            .inner
+            .get(
+                format!("/repos/{}/{}/pulls/{}", self.org, repo, number),
+                None::<&()>,
+            )
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        if pr.mergeable == Some(false) || pr.mergeable_state == Some(MergeableState::Dirty) {
+            return Ok(Some(Staleness::Conflicting));
+        }
+        let compare: CompareResp = self
+            .inner
+            .get(
+                format!("/repos/{}/{}/compare/{}...{}", self.org, repo, base, head),
+                None::<&()>,
+            )
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        Ok((compare.behind_by > 0).then_some(Staleness::Behind(compare.behind_by)))
+    }
+
+    /// Leaves `comment` on a PR and closes it.
+    pub async fn close_pull_request(&self, repo: &str, number: u64, comment: &str) -> Result<()> {
+        let _: serde_json::Value = self
+            .inner
+            .post(
+                format!("/repos/{}/{}/issues/{}/comments", self.org, repo, number),
+                Some(&serde_json::json!({ "body": comment })),
+            )
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        let _: serde_json::Value = self
+            .inner
+            .patch(
+                format!("/repos/{}/{}/pulls/{}", self.org, repo, number),
+                Some(&serde_json::json!({ "state": "closed" })),
+            )
+            .await
+            .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
+    pub async fn delete_branch(&self, repo: &str, branch: &str) -> Result<()> {
+        let result = self
+            .inner
+            .delete(
+                format!("/repos/{}/{}/git/refs/heads/{}", self.org, repo, branch),
+                None::<&()>,
+            )
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))?;
+        Ok(())
+    }
+
     pub async fn create_branch_from(
         &self,
         repo: &str,
@@ -535,7 +2091,11 @@ impl GithubClient {
             sha: base_sha,
         };
         let path = format!("/repos/{}/{}/git/refs", self.org, repo);
-        match self.inner._post(path, Some(&body)).await {
+        match self
+            .inner
+            .post::<_, serde_json::Value>(path, Some(&body))
+            .await
+        {
             Ok(_) => Ok(()),
             Err(octocrab::Error::GitHub { ref source, .. })
                 if source.status_code == reqwest::StatusCode::UNPROCESSABLE_ENTITY =>
@@ -555,7 +2115,7 @@ impl GithubClient {
         base: &str,
         body: Option<&str>,
         draft: bool,
-    ) -> Result<()> {
+    ) -> Result<PullRequest> {
         #[derive(Serialize)]
         struct Body<'a> {
             title: &'a str,
@@ -572,14 +2132,83 @@ impl GithubClient {
             body,
             draft,
         };
-        match self
-            .inner
-            ._post(format!("/repos/{}/{}/pulls", self.org, repo), Some(&body))
+        self.inner
+            .post(format!("/repos/{}/{}/pulls", self.org, repo), Some(&body))
             .await
-        {
-            Ok(_) => Ok(()),
-            Err(e) => Err(map_repo_error(&self.org, repo, e)),
+            .map_err(|e| map_repo_error(&self.org, repo, e))
+    }
+
+    /// Adds labels, assignees and review requests to a freshly opened PR. Empty lists are
+    /// skipped.
+    pub async fn decorate_pull_request(
+        &self,
+        repo: &str,
+        number: u64,
+        labels: &[String],
+        assignees: &[String],
+        reviewers: &[String],
+        team_reviewers: &[String],
+    ) -> Result<()> {
+        if !labels.is_empty() {
+            let _: serde_json::Value = self
+                .inner
+                .post(
+                    format!("/repos/{}/{}/issues/{}/labels", self.org, repo, number),
+                    Some(&serde_json::json!({ "labels": labels })),
+                )
+                .await
+                .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        }
+        if !assignees.is_empty() {
+            let _: serde_json::Value = self
+                .inner
+                .post(
+                    format!("/repos/{}/{}/issues/{}/assignees", self.org, repo, number),
+                    Some(&serde_json::json!({ "assignees": assignees })),
+                )
+                .await
+                .map_err(|e| map_repo_error(&self.org, repo, e))?;
+        }
+        if !reviewers.is_empty() || !team_reviewers.is_empty() {
+            let _: serde_json::Value = self
+                .inner
+                .post(
+                    format!(
+                        "/repos/{}/{}/pulls/{}/requested_reviewers",
+                        self.org, repo, number
+                    ),
+                    Some(&serde_json::json!({
+                        "reviewers": reviewers,
+                        "team_reviewers": team_reviewers,
+                    })),
+                )
+                .await
+                .map_err(|e| map_repo_error(&self.org, repo, e))?;
         }
+        Ok(())
+    }
+
+    /// Asks GitHub to merge the PR once its required checks pass. The repository must allow
+    /// auto-merge and the PR must not be a draft.
+    pub async fn enable_auto_merge(
+        &self,
+        repo: &str,
+        pr_node_id: &str,
+        method: AutoMergeMethod,
+    ) -> Result<()> {
+        let query = r#"
+            mutation($id: ID!, $method: PullRequestMergeMethod!) {
+              enablePullRequestAutoMerge(input: { pullRequestId: $id, mergeMethod: $method }) {
+                clientMutationId
+              }
+            }"#;
+        self.graphql_query(
+            repo,
+            query,
+            serde_json::json!({ "id": pr_node_id, "method": method.graphql_name() }),
+        )
+        .await?;
+        Ok(())
     }
 
     pub async fn find_open_pr_by_head_prefix(
@@ -637,20 +2266,108 @@ impl GithubClient {
             body: Option<&'a str>,
         }
         let body = Body { title, body };
-        match self
+        let result = self
             .inner
-            ._patch(
+            .patch(
                 format!("/repos/{}/{}/pulls/{}", self.org, repo, number),
                 Some(&body),
             )
+            .await;
+        no_content(result, |e| map_repo_error(&self.org, repo, e))
+    }
+
+    /// Collects every page of a list endpoint that does not have a typed Octocrab builder.
+    async fn get_all_pages<T: DeserializeOwned>(&self, repo: &str, route: &str) -> Result<Vec<T>> {
+        self.fetch_all_pages(route)
             .await
-        {
-            Ok(_) => Ok(()),
+            .map_err(|e| map_repo_error(&self.org, repo, e))
+    }
+
+    async fn get_all_org_pages<T: DeserializeOwned>(&self, route: &str) -> Result<Vec<T>> {
+        Ok(self.fetch_all_pages(route).await?)
+    }
+
+    async fn fetch_all_pages<T: DeserializeOwned>(
+        &self,
+        route: &str,
+    ) -> std::result::Result<Vec<T>, octocrab::Error> {
+        const PER_PAGE: usize = 100;
+        let sep = if route.contains('?') { '&' } else { '?' };
+        let mut items = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let batch: Vec<T> = self
+                .inner
+                .get(
+                    format!("{route}{sep}per_page={PER_PAGE}&page={page}"),
+                    None::<&()>,
+                )
+                .await?;
+            let done = batch.len() < PER_PAGE;
+            items.extend(batch);
+            if done {
+                break;
+            }
+            page += 1;
+        }
+        Ok(items)
+    }
+
+    /// GET helper for endpoints that may legitimately be unavailable for a repository
+    /// (feature not enabled, insufficient plan or permissions); those cases yield `None`.
+    async fn get_optional<T: DeserializeOwned>(
+        &self,
+        repo: &str,
+        route: &str,
+        what: &str,
+    ) -> Result<Option<T>> {
+        match self.inner.get::<T, _, ()>(route, None).await {
+            Ok(value) => Ok(Some(value)),
+            Err(octocrab::Error::GitHub { ref source, .. })
+                if source.status_code == reqwest::StatusCode::NOT_FOUND
+                    || source.status_code == reqwest::StatusCode::UNPROCESSABLE_ENTITY =>
+            {
+                Ok(None)
+            }
+            Err(octocrab::Error::GitHub { ref source, .. })
+                if source.status_code == reqwest::StatusCode::FORBIDDEN =>
+            {
+                warn!(
+                    "{} not available for {}/{}: {}",
+                    what, self.org, repo, source.message
+                );
+                Ok(None)
+            }
             Err(e) => Err(map_repo_error(&self.org, repo, e)),
         }
     }
 }
 
+fn webhook_config(hook: &WebhookSpec, secret: Option<&str>) -> serde_json::Value {
+    let mut config = serde_json::json!({ "url": hook.url });
+    if let Some(content_type) = hook.content_type {
+        config["content_type"] = serde_json::json!(content_type);
+    }
+    if let Some(insecure) = hook.insecure_ssl {
+        config["insecure_ssl"] = serde_json::json!(if insecure { "1" } else { "0" });
+    }
+    if let Some(secret) = secret {
+        config["secret"] = serde_json::json!(secret);
+    }
+    config
+}
+
+fn webhook_body(hook: &WebhookSpec) -> serde_json::Value {
+    let mut body = serde_json::json!({});
+    if let Some(events) = &hook.events {
+        body["events"] = serde_json::json!(events);
+    }
+    if let Some(active) = hook.active {
+        body["active"] = serde_json::json!(active);
+    }
+    body
+}
+
 fn normalize_color(color: &Option<String>) -> String {
     color
         .as_ref()
@@ -680,6 +2397,32 @@ where
     Ok(items)
 }
 
+fn milestone_body(milestone: &MilestoneSpec) -> serde_json::Value {
+    let mut body = serde_json::json!({ "title": milestone.title });
+    if let Some(description) = &milestone.description {
+        body["description"] = serde_json::json!(description);
+    }
+    if let Some(due_on) = &milestone.due_on {
+        body["due_on"] = serde_json::json!(format!("{due_on}T00:00:00Z"));
+    }
+    if let Some(state) = milestone.state {
+        body["state"] = serde_json::json!(state);
+    }
+    body
+}
+
+/// Typed octocrab calls turn error statuses into `Err`, unlike the raw `_put`/`_delete`
+/// family. A success without a body (204) cannot be parsed as JSON and still counts.
+fn no_content(
+    result: octocrab::Result<serde_json::Value>,
+    map_err: impl FnOnce(octocrab::Error) -> Error,
+) -> Result<()> {
+    match result {
+        Ok(_) | Err(octocrab::Error::Json { .. }) => Ok(()),
+        Err(e) => Err(map_err(e)),
+    }
+}
+
 fn map_repo_error(org: &str, repo: &str, err: octocrab::Error) -> Error {
     if let octocrab::Error::GitHub { source, .. } = &err {
         if source.status_code == reqwest::StatusCode::NOT_FOUND {
@@ -689,7 +2432,7 @@ fn map_repo_error(org: &str, repo: &str, err: octocrab::Error) -> Error {
             };
         }
     }
-    Error::Octo(err)
+    Error::from(err)
 }
 
 fn collect_issue_refs(issues: &[Issue]) -> Vec<LabelUsageEntry> {
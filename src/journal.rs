@@ -0,0 +1,293 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::github::{FileChange, GithubClient};
+use crate::sets::LabelSpec;
+use crate::settings::{BranchProtectionRule, RepoSettings};
+
+/// One line of the apply journal. Each mutation is recorded with the state it replaces before
+/// it is sent to GitHub, so an interrupted run can still be rolled back; mutations without a
+/// restorable before-state are recorded as [`JournalEntry::Unrestorable`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalEntry {
+    /// First line of every journal.
+    Run {
+        org: String,
+        started_at: String,
+    },
+    /// `before: None` means the label was created; `after: None` means it was deleted. A
+    /// rename shows as different names in `before` and `after`.
+    Label {
+        repo: String,
+        before: Option<LabelSpec>,
+        after: Option<LabelSpec>,
+    },
+    RepoSettings {
+        repo: String,
+        before: RepoSettings,
+    },
+    /// `before: None` means the branch was not protected.
+    BranchProtection {
+        repo: String,
+        pattern: String,
+        before: Option<BranchProtectionRule>,
+    },
+    /// Files committed to `branch` in one commit.
+    Files {
+        repo: String,
+        branch: String,
+        files: Vec<FileBefore>,
+    },
+    /// A change rollback cannot undo (access, webhooks, environments, autolinks, custom
+    /// properties, milestones, discussions, org settings and teams). `repo: None` marks an
+    /// org-wide change. Rollback lists these so they can be reverted by hand.
+    Unrestorable {
+        repo: Option<String>,
+        change: String,
+    },
+}
+
+/// A file as it was before gh-governor committed to it; `sha` and `contents` are `None`
+/// for files that did not exist.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileBefore {
+    pub path: String,
+    pub sha: Option<String>,
+    pub contents: Option<String>,
+}
+
+impl JournalEntry {
+    pub fn repo(&self) -> Option<&str> {
+        match self {
+            JournalEntry::Run { .. } => None,
+            JournalEntry::Label { repo, .. }
+            | JournalEntry::RepoSettings { repo, .. }
+            | JournalEntry::BranchProtection { repo, .. }
+            | JournalEntry::Files { repo, .. } => Some(repo),
+            JournalEntry::Unrestorable { repo, .. } => repo.as_deref(),
+        }
+    }
+}
+
+/// Append-only JSON Lines journal written during `apply`. A journal without a file (used by
+/// `plan`) ignores all records.
+#[derive(Debug, Default)]
+pub struct Journal {
+    file: Option<(File, PathBuf)>,
+}
+
+impl Journal {
+    pub fn create(path: &Path, org: &str) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| Error::io_with_path(e, parent.to_path_buf()))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| Error::io_with_path(e, path.to_path_buf()))?;
+        let mut journal = Journal {
+            file: Some((file, path.to_path_buf())),
+        };
+        journal.record(&JournalEntry::Run {
+            org: org.to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+        })?;
+        Ok(journal)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.file.as_ref().map(|(_, path)| path.as_path())
+    }
+
+    /// Records a change that rollback can only report, not undo.
+    pub fn record_unrestorable(&mut self, repo: Option<&str>, change: String) -> Result<()> {
+        self.record(&JournalEntry::Unrestorable {
+            repo: repo.map(str::to_string),
+            change,
+        })
+    }
+
+    pub fn record(&mut self, entry: &JournalEntry) -> Result<()> {
+        let Some((file, path)) = self.file.as_mut() else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(|e| Error::io_with_path(e, path.clone()))
+    }
+}
+
+pub fn read_journal(path: &Path) -> Result<Vec<JournalEntry>> {
+    let file = File::open(path).map_err(|e| Error::io_with_path(e, path.to_path_buf()))?;
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| Error::io_with_path(e, path.to_path_buf()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(
+            serde_json::from_str(&line)
+                .map_err(|e| Error::json_with_path(e, path.to_path_buf()))?,
+        );
+    }
+    Ok(entries)
+}
+
+/// Restores the recorded before-state, newest mutation first. With `only_repos` non-empty,
+/// entries for other repositories and org-wide changes are skipped. Changes that cannot be
+/// restored are listed at the end.
+pub async fn rollback(
+    gh: &GithubClient,
+    entries: &[JournalEntry],
+    only_repos: &[String],
+) -> Result<()> {
+    let mut manual = Vec::new();
+    for entry in entries.iter().rev() {
+        if let JournalEntry::Unrestorable { repo, change } = entry {
+            match repo {
+                Some(repo) if only_repos.is_empty() || only_repos.iter().any(|r| r == repo) => {
+                    manual.push(format!("repo {repo}: {change}"))
+                }
+                None if only_repos.is_empty() => manual.push(format!("org: {change}")),
+                _ => {}
+            }
+            continue;
+        }
+        let Some(repo) = entry.repo() else { continue };
+        if !only_repos.is_empty() && !only_repos.iter().any(|r| r == repo) {
+            continue;
+        }
+        match entry {
+            JournalEntry::Run { .. } | JournalEntry::Unrestorable { .. } => {}
+            JournalEntry::Label { before, after, .. } => match (before, after) {
+                (None, Some(created)) => {
+                    gh.delete_label(repo, &created.name).await?;
+                    println!("Repo {repo} (rollback): deleted label '{}'", created.name);
+                }
+                (Some(previous), Some(applied)) => {
+                    // An unset description is left alone by updates, so clear it explicitly.
+                    let restored = LabelSpec {
+                        description: Some(previous.description.clone().unwrap_or_default()),
+                        ..previous.clone()
+                    };
+                    // The label carries the applied name, so a rename is undone too.
+                    gh.update_label(repo, &applied.name, &restored).await?;
+                    println!("Repo {repo} (rollback): restored label '{}'", previous.name);
+                }
+                (Some(previous), None) => {
+                    gh.create_label(repo, previous).await?;
+                    println!(
+                        "Repo {repo} (rollback): recreated label '{}'",
+                        previous.name
+                    );
+                }
+                (None, None) => {}
+            },
+            JournalEntry::RepoSettings { before, .. } => {
+                gh.update_repo_settings(repo, before).await?;
+                if let Some(actions) = &before.actions {
+                    gh.update_actions_settings(repo, actions).await?;
+                }
+                if let Some(security) = &before.security {
+                    gh.update_security_settings(repo, security).await?;
+                }
+                println!("Repo {repo} (rollback): restored repo settings");
+            }
+            JournalEntry::BranchProtection {
+                pattern, before, ..
+            } => {
+                match before {
+                    Some(rule) => gh.set_branch_protection(repo, rule).await?,
+                    None => gh.delete_branch_protection(repo, pattern).await?,
+                }
+                println!("Repo {repo} (rollback): restored branch protection for '{pattern}'");
+            }
+            JournalEntry::Files { branch, files, .. } => {
+                let changes: Vec<FileChange> = files
+                    .iter()
+                    .map(|f| FileChange {
+                        path: f.path.clone(),
+                        contents: f.contents.clone(),
+                    })
+                    .collect();
+                let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+                let message = format!(
+                    "Roll back gh-governor file changes\n\n- {}",
+                    paths.join("\n- ")
+                );
+                gh.commit_files(repo, branch, &changes, &message).await?;
+                println!(
+                    "Repo {repo} (rollback): restored {} file(s) on '{branch}'",
+                    files.len()
+                );
+            }
+        }
+    }
+    if !manual.is_empty() {
+        eprintln!(
+            "Rollback cannot undo {} change(s); revert them by hand:\n  - {}",
+            manual.len(),
+            manual.join("\n  - ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal_round_trips_entries() {
+        let path = std::env::temp_dir().join(format!(
+            "gh-governor-journal-test-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let label = JournalEntry::Label {
+            repo: "api".to_string(),
+            before: None,
+            after: Some(LabelSpec {
+                name: "bug".to_string(),
+                color: Some("d73a4a".to_string()),
+                description: None,
+            }),
+        };
+        let files = JournalEntry::Files {
+            repo: "api".to_string(),
+            branch: "main".to_string(),
+            files: vec![FileBefore {
+                path: ".github/ISSUE_TEMPLATE/bug.yml".to_string(),
+                sha: Some("abc".to_string()),
+                contents: Some("name: Bug\n".to_string()),
+            }],
+        };
+        let team = JournalEntry::Unrestorable {
+            repo: None,
+            change: "created team platform".to_string(),
+        };
+        {
+            let mut journal = Journal::create(&path, "acme").unwrap();
+            journal.record(&label).unwrap();
+            journal.record(&files).unwrap();
+            journal
+                .record_unrestorable(None, "created team platform".to_string())
+                .unwrap();
+        }
+        let entries = read_journal(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(&entries[0], JournalEntry::Run { org, .. } if org == "acme"));
+        assert_eq!(entries[1..], [label, files, team]);
+        assert_eq!(entries[1].repo(), Some("api"));
+        assert_eq!(entries[3].repo(), None);
+    }
+}
@@ -2,9 +2,13 @@ pub mod app;
 pub mod config;
 pub mod diff;
 pub mod error;
+pub mod forms;
 pub mod generate;
 pub mod github;
+pub mod journal;
 pub mod merge;
+pub mod report;
 pub mod sets;
 pub mod settings;
 pub mod util;
+pub mod yaml;
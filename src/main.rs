@@ -2,10 +2,11 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand, ValueEnum};
 
-use gh_governor::app::{Mode, run};
+use gh_governor::app::{Mode, ReportFormat, RunOptions, run};
 use gh_governor::config::{load_root_config, resolve_sets_dir};
 use gh_governor::error::Result;
 use gh_governor::github::GithubClient;
+use gh_governor::journal::JournalEntry;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
@@ -31,20 +32,51 @@ struct Args {
 enum Command {
     /// Validate and show the merged configuration for repos (dry-run)
     Plan {
-        /// Limit to specific repositories; if omitted, all repos in config are used
+        /// Limit to specific repositories (skips org settings and teams); if omitted, all repos in config are used
         #[arg(long = "repo", value_name = "NAME")]
         repos: Vec<String>,
         /// Directory containing gh-governor-conf.(toml|yml|yaml|json) and config-sets/
         #[arg(long, default_value = ".")]
         config_base: PathBuf,
+        /// Delete teams that are not declared in the teams file
+        #[arg(long)]
+        prune_teams: bool,
+        /// Print a unified diff for each .github file that would be added or updated
+        #[arg(long)]
+        show_diff: bool,
+        /// Maximum diff lines printed per file with --show-diff (0 for no limit)
+        #[arg(long, value_name = "N", default_value_t = 40)]
+        diff_max_lines: usize,
+        /// Output format for the plan (text|markdown|junit|sarif)
+        #[arg(long, value_enum, default_value = "text")]
+        output: ReportFormatArg,
     },
     /// Apply changes (creates/updates labels and settings)
     Apply {
+        /// Limit to specific repositories (skips org settings and teams)
         #[arg(long = "repo", value_name = "NAME")]
         repos: Vec<String>,
         /// Directory containing gh-governor-conf.(toml|yml|yaml|json) and config-sets/
         #[arg(long, default_value = ".")]
         config_base: PathBuf,
+        /// Delete teams that are not declared in the teams file
+        #[arg(long)]
+        prune_teams: bool,
+        /// Keep going after a failed step or repository and print a summary at the end
+        #[arg(long)]
+        keep_going: bool,
+        /// Journal file for recorded changes (defaults to .gh-governor/journal-<timestamp>.jsonl)
+        #[arg(long, value_name = "PATH")]
+        journal: Option<PathBuf>,
+    },
+    /// Restore the state recorded in an apply journal
+    Rollback {
+        /// Journal file written by apply
+        #[arg(long, value_name = "PATH")]
+        journal: PathBuf,
+        /// Limit to specific repositories; if omitted, the whole run is rolled back
+        #[arg(long = "repo", value_name = "NAME")]
+        repos: Vec<String>,
     },
     /// Generate config files from existing repositories
     Generate {
@@ -80,6 +112,25 @@ impl From<OutputFormatArg> for gh_governor::generate::OutputFormat {
     }
 }
 
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum ReportFormatArg {
+    Text,
+    Markdown,
+    Junit,
+    Sarif,
+}
+
+impl From<ReportFormatArg> for ReportFormat {
+    fn from(val: ReportFormatArg) -> Self {
+        match val {
+            ReportFormatArg::Text => ReportFormat::Text,
+            ReportFormatArg::Markdown => ReportFormat::Markdown,
+            ReportFormatArg::Junit => ReportFormat::Junit,
+            ReportFormatArg::Sarif => ReportFormat::Sarif,
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
@@ -87,39 +138,69 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt()
         .with_env_filter(env_filter)
         .with_target(false)
+        .with_writer(std::io::stderr)
         .init();
 
     let args = Args::parse();
     match args.command {
-        Command::Plan { repos, config_base } => {
+        Command::Plan {
+            repos,
+            config_base,
+            prune_teams,
+            show_diff,
+            diff_max_lines,
+            output,
+        } => {
             let (root, root_path) = load_root_config(&config_base)?;
             let sets_dir = resolve_sets_dir(&config_base, &root);
             let gh = GithubClient::new(&args.token, root.org.clone())?;
-            run(
-                Mode::Plan,
-                root,
-                root_path,
-                sets_dir,
-                repos,
-                gh,
-                args.verbose,
-            )
-            .await
+            let options = RunOptions {
+                only_repos: repos,
+                verbose: args.verbose,
+                prune_teams,
+                show_diff,
+                diff_max_lines,
+                report: output.into(),
+                ..Default::default()
+            };
+            run(Mode::Plan, root, root_path, sets_dir, gh, options).await
         }
-        Command::Apply { repos, config_base } => {
+        Command::Apply {
+            repos,
+            config_base,
+            prune_teams,
+            keep_going,
+            journal,
+        } => {
             let (root, root_path) = load_root_config(&config_base)?;
             let sets_dir = resolve_sets_dir(&config_base, &root);
             let gh = GithubClient::new(&args.token, root.org.clone())?;
-            run(
-                Mode::Apply,
-                root,
-                root_path,
-                sets_dir,
-                repos,
-                gh,
-                args.verbose,
-            )
-            .await
+            let options = RunOptions {
+                only_repos: repos,
+                verbose: args.verbose,
+                prune_teams,
+                keep_going,
+                journal,
+                ..Default::default()
+            };
+            run(Mode::Apply, root, root_path, sets_dir, gh, options).await
+        }
+        Command::Rollback { journal, repos } => {
+            let entries = gh_governor::journal::read_journal(&journal)?;
+            let org = entries
+                .iter()
+                .find_map(|entry| match entry {
+                    JournalEntry::Run { org, .. } => Some(org.clone()),
+                    _ => None,
+                })
+                .ok_or_else(|| {
+                    gh_governor::error::Error::InvalidArgs(format!(
+                        "{} is not an apply journal",
+                        journal.display()
+                    ))
+                })?;
+            let gh = GithubClient::new(&args.token, org)?;
+            gh_governor::journal::rollback(&gh, &entries, &repos).await
         }
         Command::Generate {
             repos,
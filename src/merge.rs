@@ -1,9 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use thiserror::Error;
 
-use crate::sets::{ChecksConfig, IssueTemplateFile, LabelSpec, SetDefinition};
-use crate::settings::RepoSettings;
+use crate::config::PullRequestConfig;
+use crate::sets::{ChecksConfig, IssueTemplateFile, LabelSpec, MilestonesConfig, SetDefinition};
+use crate::settings::{
+    AccessConfig, AutolinksConfig, CustomProperties, DiscussionsConfig, EnvironmentsConfig,
+    FileDelivery, Permission, RepoSettings, WebhooksConfig,
+};
 
 #[derive(Debug, Error)]
 pub enum MergeError {
@@ -23,13 +27,34 @@ pub struct MergedRepoConfig {
     pub issue_templates: Vec<IssueTemplateFile>,
     pub repo_settings: Option<RepoSettings>,
     pub checks: Option<ChecksConfig>,
+    pub access: Option<AccessConfig>,
+    pub webhooks: Option<WebhooksConfig>,
+    pub environments: Option<EnvironmentsConfig>,
+    pub autolinks: Option<AutolinksConfig>,
+    pub properties: Option<CustomProperties>,
+    pub milestones: Option<MilestonesConfig>,
+    pub discussions: Option<DiscussionsConfig>,
+    pub file_delivery: Option<FileDelivery>,
+    /// Set that first provided each `.github` file, keyed by path.
+    pub file_sources: BTreeMap<String, String>,
+    /// Effective governance PR options; filled in from the root and repo config.
+    pub pull_request: PullRequestConfig,
 }
 
 pub fn merge_sets_for_repo(sets: &[SetDefinition]) -> MergeResult<MergedRepoConfig> {
     let mut labels = HashMap::new();
     let mut templates = HashMap::new();
+    let mut file_sources = BTreeMap::new();
     let mut repo_settings: Option<RepoSettings> = None;
     let mut checks: Option<ChecksConfig> = None;
+    let mut access: Option<AccessConfig> = None;
+    let mut webhooks: Option<WebhooksConfig> = None;
+    let mut environments: Option<EnvironmentsConfig> = None;
+    let mut autolinks: Option<AutolinksConfig> = None;
+    let mut properties: Option<CustomProperties> = None;
+    let mut milestones: Option<MilestonesConfig> = None;
+    let mut discussions: Option<DiscussionsConfig> = None;
+    let mut file_delivery: Option<FileDelivery> = None;
 
     for set in sets {
         for label in &set.labels {
@@ -51,6 +76,7 @@ pub fn merge_sets_for_repo(sets: &[SetDefinition]) -> MergeResult<MergedRepoConf
                 Some(_) => {}
                 None => {
                     templates.insert(template.path.clone(), template.clone());
+                    file_sources.insert(template.path.clone(), set.name.clone());
                 }
             }
         }
@@ -62,6 +88,123 @@ pub fn merge_sets_for_repo(sets: &[SetDefinition]) -> MergeResult<MergedRepoConf
         if let Some(chk) = &set.checks {
             checks = merge_or_conflict(checks, chk.clone(), "checks")?;
         }
+
+        if let Some(acc) = &set.access {
+            access = Some(merge_access(access.unwrap_or_default(), acc)?);
+        }
+
+        if let Some(hooks) = &set.webhooks {
+            webhooks = Some(merge_webhooks(webhooks.unwrap_or_default(), hooks)?);
+        }
+
+        if let Some(envs) = &set.environments {
+            let mut merged = environments.unwrap_or_default();
+            for env in &envs.environments {
+                match merged.environments.iter().find(|e| e.name == env.name) {
+                    Some(existing) if existing != env => {
+                        return Err(MergeError::GenericConflict(format!(
+                            "environment '{}'",
+                            env.name
+                        )));
+                    }
+                    Some(_) => {}
+                    None => merged.environments.push(env.clone()),
+                }
+            }
+            environments = Some(merged);
+        }
+
+        if let Some(links) = &set.autolinks {
+            let mut merged = autolinks.unwrap_or_default();
+            if let Some(mode) = links.mode {
+                merged.mode = merge_or_conflict(merged.mode, mode, "autolinks mode")?;
+            }
+            for link in &links.autolinks {
+                match merged
+                    .autolinks
+                    .iter()
+                    .find(|l| l.key_prefix == link.key_prefix)
+                {
+                    Some(existing) if existing != link => {
+                        return Err(MergeError::GenericConflict(format!(
+                            "autolink '{}'",
+                            link.key_prefix
+                        )));
+                    }
+                    Some(_) => {}
+                    None => merged.autolinks.push(link.clone()),
+                }
+            }
+            autolinks = Some(merged);
+        }
+
+        if let Some(cfg) = &set.milestones {
+            let mut merged = milestones.unwrap_or_default();
+            if let Some(mode) = cfg.mode {
+                merged.mode = merge_or_conflict(merged.mode, mode, "milestones mode")?;
+            }
+            for milestone in &cfg.milestones {
+                match merged
+                    .milestones
+                    .iter()
+                    .find(|m| m.title == milestone.title)
+                {
+                    Some(existing) if existing != milestone => {
+                        return Err(MergeError::GenericConflict(format!(
+                            "milestone '{}'",
+                            milestone.title
+                        )));
+                    }
+                    Some(_) => {}
+                    None => merged.milestones.push(milestone.clone()),
+                }
+            }
+            milestones = Some(merged);
+        }
+
+        if let Some(cfg) = &set.discussions {
+            let mut merged = discussions.unwrap_or_default();
+            if let Some(enabled) = cfg.enabled {
+                merged.enabled = merge_or_conflict(merged.enabled, enabled, "discussions enabled")?;
+            }
+            if let Some(mode) = cfg.mode {
+                merged.mode = merge_or_conflict(merged.mode, mode, "discussions mode")?;
+            }
+            for category in &cfg.categories {
+                match merged.categories.iter().find(|c| c.name == category.name) {
+                    Some(existing) if existing != category => {
+                        return Err(MergeError::GenericConflict(format!(
+                            "discussion category '{}'",
+                            category.name
+                        )));
+                    }
+                    Some(_) => {}
+                    None => merged.categories.push(category.clone()),
+                }
+            }
+            discussions = Some(merged);
+        }
+
+        if let Some(delivery) = set.file_delivery {
+            file_delivery = merge_or_conflict(file_delivery, delivery, "file delivery")?;
+        }
+
+        if let Some(values) = &set.properties {
+            let merged = properties.get_or_insert_with(CustomProperties::new);
+            for (name, value) in values {
+                match merged.get(name) {
+                    Some(existing) if existing != value => {
+                        return Err(MergeError::GenericConflict(format!(
+                            "custom property '{name}'"
+                        )));
+                    }
+                    Some(_) => {}
+                    None => {
+                        merged.insert(name.clone(), value.clone());
+                    }
+                }
+            }
+        }
     }
 
     Ok(MergedRepoConfig {
@@ -77,9 +220,82 @@ pub fn merge_sets_for_repo(sets: &[SetDefinition]) -> MergeResult<MergedRepoConf
         },
         repo_settings,
         checks,
+        access,
+        webhooks,
+        environments,
+        autolinks,
+        properties,
+        milestones,
+        discussions,
+        file_delivery,
+        file_sources,
+        pull_request: PullRequestConfig::default(),
     })
 }
 
+/// Access entries from several sets are combined; the same team or collaborator may only be
+/// granted one permission.
+fn merge_access(mut merged: AccessConfig, incoming: &AccessConfig) -> MergeResult<AccessConfig> {
+    if let Some(mode) = incoming.mode {
+        merged.mode = merge_or_conflict(merged.mode, mode, "access mode")?;
+    }
+    // GitHub logins and team slugs are case-insensitive.
+    let find = |map: &BTreeMap<String, Permission>, name: &str| {
+        map.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| *v)
+    };
+    for (slug, perm) in &incoming.teams {
+        match find(&merged.teams, slug) {
+            Some(existing) if existing != *perm => {
+                return Err(MergeError::GenericConflict(format!(
+                    "access for team '{slug}'"
+                )));
+            }
+            Some(_) => {}
+            None => {
+                merged.teams.insert(slug.clone(), *perm);
+            }
+        }
+    }
+    for (login, perm) in &incoming.collaborators {
+        match find(&merged.collaborators, login) {
+            Some(existing) if existing != *perm => {
+                return Err(MergeError::GenericConflict(format!(
+                    "access for collaborator '{login}'"
+                )));
+            }
+            Some(_) => {}
+            None => {
+                merged.collaborators.insert(login.clone(), *perm);
+            }
+        }
+    }
+    Ok(merged)
+}
+
+fn merge_webhooks(
+    mut merged: WebhooksConfig,
+    incoming: &WebhooksConfig,
+) -> MergeResult<WebhooksConfig> {
+    if let Some(mode) = incoming.mode {
+        merged.mode = merge_or_conflict(merged.mode, mode, "webhooks mode")?;
+    }
+    for hook in &incoming.hooks {
+        match merged.hooks.iter().find(|h| h.url == hook.url) {
+            Some(existing) if existing != hook => {
+                return Err(MergeError::GenericConflict(format!(
+                    "webhook '{}'",
+                    hook.url
+                )));
+            }
+            Some(_) => {}
+            None => merged.hooks.push(hook.clone()),
+        }
+    }
+    Ok(merged)
+}
+
 fn merge_or_conflict<T: PartialEq>(
     existing: Option<T>,
     incoming: T,
@@ -103,8 +319,17 @@ mod tests {
             path: "".into(),
             labels: Vec::new(),
             issue_templates: Vec::new(),
+            template_labels: BTreeMap::new(),
             repo_settings: None,
             checks: None,
+            access: None,
+            webhooks: None,
+            environments: None,
+            autolinks: None,
+            properties: None,
+            milestones: None,
+            discussions: None,
+            file_delivery: None,
         }
     }
 
@@ -178,5 +403,58 @@ mod tests {
         });
         let merged = merge_sets_for_repo(&[a, b]).unwrap();
         assert_eq!(merged.issue_templates.len(), 1);
+        assert_eq!(
+            merged.file_sources[".github/ISSUE_TEMPLATE/bug.yml"],
+            "a".to_string()
+        );
+    }
+
+    #[test]
+    fn file_delivery_must_agree_between_sets() {
+        let mut a = base_set("a");
+        a.file_delivery = Some(FileDelivery::Direct);
+        let b = base_set("b");
+        let merged = merge_sets_for_repo(&[a.clone(), b]).unwrap();
+        assert_eq!(merged.file_delivery, Some(FileDelivery::Direct));
+
+        let mut c = base_set("c");
+        c.file_delivery = Some(FileDelivery::Pr);
+        assert!(matches!(
+            merge_sets_for_repo(&[a, c]),
+            Err(MergeError::GenericConflict(what)) if what == "file delivery"
+        ));
+    }
+
+    #[test]
+    fn merges_access_and_detects_permission_conflict() {
+        let mut a = base_set("a");
+        let mut acc_a = AccessConfig::default();
+        acc_a
+            .teams
+            .insert("platform".to_string(), Permission::Maintain);
+        a.access = Some(acc_a);
+        let mut b = base_set("b");
+        let mut acc_b = AccessConfig::default();
+        acc_b.teams.insert("qa".to_string(), Permission::Triage);
+        b.access = Some(acc_b.clone());
+        let merged = merge_sets_for_repo(&[a.clone(), b]).unwrap();
+        assert_eq!(merged.access.unwrap().teams.len(), 2);
+
+        let mut c = base_set("c");
+        acc_b.teams.insert("platform".to_string(), Permission::Push);
+        c.access = Some(acc_b);
+        assert!(matches!(
+            merge_sets_for_repo(&[a.clone(), c]),
+            Err(MergeError::GenericConflict(_))
+        ));
+
+        let mut d = base_set("d");
+        let mut acc_d = AccessConfig::default();
+        acc_d.teams.insert("Platform".to_string(), Permission::Push);
+        d.access = Some(acc_d);
+        assert!(matches!(
+            merge_sets_for_repo(&[a, d]),
+            Err(MergeError::GenericConflict(what)) if what == "access for team 'Platform'"
+        ));
     }
 }
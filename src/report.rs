@@ -0,0 +1,534 @@
+use crate::github::LabelUsageEntry;
+
+/// Plan results for one repository, collected for the non-text output formats.
+#[derive(Debug, Clone, Default)]
+pub struct RepoReport {
+    pub repo: String,
+    pub sections: Vec<ReportSection>,
+    pub blocked: Vec<BlockedLabel>,
+    /// What happens to the governance PR (or direct commit) for `.github` files.
+    pub pr_note: String,
+    /// Whether a CODEOWNERS file exists; `None` when the repo's checks do not require one.
+    pub codeowners: Option<bool>,
+}
+
+/// One category of changes; `count` is `None` when the repo does not configure it.
+#[derive(Debug, Clone)]
+pub struct ReportSection {
+    pub name: &'static str,
+    pub count: Option<usize>,
+    /// Markdown list lines, already indented for nesting.
+    pub details: Vec<String>,
+}
+
+/// Plan results for organization settings and teams; empty when neither is configured or the
+/// run is limited to some repositories.
+#[derive(Debug, Clone, Default)]
+pub struct OrgReport {
+    pub sections: Vec<ReportSection>,
+}
+
+impl OrgReport {
+    pub fn policy_results(&self) -> Vec<PolicyResult> {
+        ORG_POLICIES
+            .iter()
+            .zip(["Org settings", "Teams"])
+            .filter_map(|(policy, name)| {
+                let section = self.sections.iter().find(|s| s.name == name)?;
+                Some(PolicyResult {
+                    policy,
+                    outcome: section_outcome(Some(section)),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Pass when a configured section has no pending changes, skipped when it is not configured.
+fn section_outcome(section: Option<&ReportSection>) -> PolicyOutcome {
+    match section.and_then(|s| s.count.map(|c| (c, s))) {
+        None => PolicyOutcome::Skipped,
+        Some((0, _)) => PolicyOutcome::Pass,
+        Some((_, s)) => PolicyOutcome::Fail(s.details.clone()),
+    }
+}
+
+/// A label that would be removed but is still used by issues or PRs.
+#[derive(Debug, Clone)]
+pub struct BlockedLabel {
+    pub label: String,
+    pub usage: Vec<LabelUsageEntry>,
+}
+
+impl RepoReport {
+    pub fn total_changes(&self) -> usize {
+        self.sections.iter().filter_map(|s| s.count).sum()
+    }
+
+    fn section(&self, name: &str) -> Option<&ReportSection> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    /// Compliance of this repo with each policy checked by the junit and sarif outputs.
+    pub fn policy_results(&self) -> Vec<PolicyResult> {
+        let in_sync = |policy: &'static Policy, section: Option<&ReportSection>| PolicyResult {
+            policy,
+            outcome: section_outcome(section),
+        };
+
+        let mut labels = in_sync(&POLICIES[0], self.section("Labels"));
+        if !self.blocked.is_empty() {
+            let mut details = match labels.outcome {
+                PolicyOutcome::Fail(details) => details,
+                _ => Vec::new(),
+            };
+            for blocked in &self.blocked {
+                details.push(format!(
+                    "- blocked removal of `{}`, still used by {}",
+                    blocked.label,
+                    usage_links(&blocked.usage)
+                ));
+            }
+            labels.outcome = PolicyOutcome::Fail(details);
+        }
+        let codeowners = PolicyResult {
+            policy: &POLICIES[3],
+            outcome: match self.codeowners {
+                None => PolicyOutcome::Skipped,
+                Some(true) => PolicyOutcome::Pass,
+                Some(false) => PolicyOutcome::Fail(vec![
+                    "- no CODEOWNERS file in the repository root, .github/ or docs/".to_string(),
+                ]),
+            },
+        };
+        vec![
+            labels,
+            in_sync(&POLICIES[1], self.section("Settings")),
+            in_sync(&POLICIES[2], self.section("Protection")),
+            codeowners,
+        ]
+    }
+}
+
+/// A compliance rule reported per repository.
+#[derive(Debug)]
+pub struct Policy {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+pub const POLICIES: [Policy; 4] = [
+    Policy {
+        id: "labels-in-sync",
+        name: "labels in sync",
+        description: "Repository labels match the merged configuration sets.",
+    },
+    Policy {
+        id: "settings-in-sync",
+        name: "settings in sync",
+        description: "Repository settings match the merged configuration sets.",
+    },
+    Policy {
+        id: "protection-in-sync",
+        name: "protection in sync",
+        description: "Branch protection rules match the merged configuration sets.",
+    },
+    Policy {
+        id: "codeowners-present",
+        name: "CODEOWNERS present",
+        description: "A CODEOWNERS file exists when the checks config requires one.",
+    },
+];
+
+/// Compliance rules reported once for the organization.
+pub const ORG_POLICIES: [Policy; 2] = [
+    Policy {
+        id: "org-settings-in-sync",
+        name: "org settings in sync",
+        description: "Organization settings match the org settings file.",
+    },
+    Policy {
+        id: "teams-in-sync",
+        name: "teams in sync",
+        description: "Teams and memberships match the teams file.",
+    },
+];
+
+#[derive(Debug)]
+pub struct PolicyResult {
+    pub policy: &'static Policy,
+    pub outcome: PolicyOutcome,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PolicyOutcome {
+    Pass,
+    /// Drift found; holds the pending changes as Markdown list lines.
+    Fail(Vec<String>),
+    /// The repo does not configure this policy.
+    Skipped,
+}
+
+/// Renders a summary table (repos × categories) followed by a collapsible section per repo.
+pub fn render_markdown(org: &str, org_report: &OrgReport, reports: &[RepoReport]) -> String {
+    let mut out = format!("## gh-governor plan for `{org}`\n\n");
+    for section in &org_report.sections {
+        let count = section.count.unwrap_or(0);
+        out.push_str(&format!(
+            "**{}:** {count} change{}\n",
+            section.name,
+            if count == 1 { "" } else { "s" }
+        ));
+        for line in &section.details {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    if reports.is_empty() {
+        out.push_str("No repositories configured.\n");
+        return out;
+    }
+
+    let names: Vec<&str> = reports[0].sections.iter().map(|s| s.name).collect();
+    out.push_str("| Repository |");
+    for name in &names {
+        out.push_str(&format!(" {name} |"));
+    }
+    out.push_str(" Blocked |\n|---|");
+    for _ in &names {
+        out.push_str("---:|");
+    }
+    out.push_str("---:|\n");
+    for report in reports {
+        out.push_str(&format!("| `{}` |", report.repo));
+        for section in &report.sections {
+            match section.count {
+                Some(count) => out.push_str(&format!(" {count} |")),
+                None => out.push_str(" – |"),
+            }
+        }
+        out.push_str(&format!(" {} |\n", report.blocked.len()));
+    }
+
+    for report in reports {
+        let total = report.total_changes();
+        out.push_str(&format!(
+            "\n<details>\n<summary><code>{}</code>: {} change{}{}</summary>\n\n",
+            report.repo,
+            total,
+            if total == 1 { "" } else { "s" },
+            if report.blocked.is_empty() {
+                String::new()
+            } else {
+                let n = report.blocked.len();
+                format!(
+                    ", {n} blocked label removal{}",
+                    if n == 1 { "" } else { "s" }
+                )
+            }
+        ));
+        out.push_str(&format!("**PR:** {}\n", report.pr_note));
+        for section in report.sections.iter().filter(|s| !s.details.is_empty()) {
+            out.push_str(&format!("\n**{}**\n\n", section.name));
+            for line in &section.details {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+        if !report.blocked.is_empty() {
+            out.push_str("\n**Blocked label removals**\n\n");
+            for blocked in &report.blocked {
+                out.push_str(&format!(
+                    "- `{}` is still used by {}\n",
+                    blocked.label,
+                    usage_links(&blocked.usage)
+                ));
+            }
+        }
+        out.push_str("\n</details>\n");
+    }
+    out
+}
+
+/// Renders one JUnit test suite per repository with a test case per policy, plus an
+/// `organization` suite when org settings or teams are configured.
+pub fn render_junit(org: &str, org_report: &OrgReport, reports: &[RepoReport]) -> String {
+    let mut suites = String::new();
+    let (mut total, mut failed, mut skipped) = (0, 0, 0);
+    let org_results = org_report.policy_results();
+    let mut all = Vec::new();
+    if !org_results.is_empty() {
+        all.push(("organization".to_string(), org.to_string(), org_results));
+    }
+    for report in reports {
+        all.push((
+            report.repo.clone(),
+            format!("{org}/{}", report.repo),
+            report.policy_results(),
+        ));
+    }
+    for (suite, classname, results) in all {
+        let suite_failed = results
+            .iter()
+            .filter(|r| matches!(r.outcome, PolicyOutcome::Fail(_)))
+            .count();
+        let suite_skipped = results
+            .iter()
+            .filter(|r| r.outcome == PolicyOutcome::Skipped)
+            .count();
+        total += results.len();
+        failed += suite_failed;
+        skipped += suite_skipped;
+        suites.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{suite_failed}\" skipped=\"{suite_skipped}\">\n",
+            xml_escape(&suite),
+            results.len()
+        ));
+        for result in results {
+            let open = format!(
+                "    <testcase classname=\"{}\" name=\"{}\"",
+                xml_escape(&classname),
+                xml_escape(result.policy.name)
+            );
+            match result.outcome {
+                PolicyOutcome::Pass => suites.push_str(&format!("{open}/>\n")),
+                PolicyOutcome::Skipped => suites.push_str(&format!(
+                    "{open}>\n      <skipped message=\"not configured\"/>\n    </testcase>\n"
+                )),
+                PolicyOutcome::Fail(details) => suites.push_str(&format!(
+                    "{open}>\n      <failure message=\"{} pending change{}\">{}</failure>\n    </testcase>\n",
+                    details.len(),
+                    if details.len() == 1 { "" } else { "s" },
+                    xml_escape(&details.join("\n"))
+                )),
+            }
+        }
+        suites.push_str("  </testsuite>\n");
+    }
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"gh-governor {}\" tests=\"{total}\" failures=\"{failed}\" skipped=\"{skipped}\">\n{suites}</testsuites>",
+        xml_escape(org)
+    )
+}
+
+/// Renders a SARIF 2.1.0 log with one result per failing repo × policy. Results point at
+/// `config_uri` since the drift is relative to that configuration.
+pub fn render_sarif(
+    org: &str,
+    config_uri: &str,
+    org_report: &OrgReport,
+    reports: &[RepoReport],
+) -> String {
+    let rules: Vec<serde_json::Value> = POLICIES
+        .iter()
+        .chain(&ORG_POLICIES)
+        .map(|p| {
+            serde_json::json!({
+                "id": p.id,
+                "name": p.name,
+                "shortDescription": { "text": p.description },
+                "defaultConfiguration": { "level": "error" },
+            })
+        })
+        .collect();
+    let mut subjects = vec![(org.to_string(), org_report.policy_results())];
+    for report in reports {
+        subjects.push((format!("{org}/{}", report.repo), report.policy_results()));
+    }
+    let mut results = Vec::new();
+    for (subject, policy_results) in subjects {
+        for result in policy_results {
+            let PolicyOutcome::Fail(details) = result.outcome else {
+                continue;
+            };
+            results.push(serde_json::json!({
+                "ruleId": result.policy.id,
+                "level": "error",
+                "message": {
+                    "text": format!(
+                        "{subject} is not compliant with '{}':\n{}",
+                        result.policy.name,
+                        details.join("\n")
+                    ),
+                },
+                "locations": [{
+                    "physicalLocation": { "artifactLocation": { "uri": config_uri } },
+                }],
+                "partialFingerprints": {
+                    "governancePolicy/v1": format!("{subject}:{}", result.policy.id),
+                },
+            }));
+        }
+    }
+    let log = serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "gh-governor",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).unwrap_or_default()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn usage_links(usage: &[LabelUsageEntry]) -> String {
+    let mut links = Vec::new();
+    let mut more = false;
+    for entry in usage {
+        if entry.number == 0 {
+            more = true;
+            continue;
+        }
+        let kind = if entry.is_pr { "PR" } else { "issue" };
+        match &entry.url {
+            Some(url) => links.push(format!("[{kind} #{}]({url})", entry.number)),
+            None => links.push(format!("{kind} #{}", entry.number)),
+        }
+    }
+    if links.is_empty() {
+        return "issues or PRs".to_string();
+    }
+    let mut text = links.join(", ");
+    if more {
+        text.push_str(" and more");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_table_and_details_with_issue_links() {
+        let report = RepoReport {
+            repo: "api".to_string(),
+            sections: vec![
+                ReportSection {
+                    name: "Settings",
+                    count: None,
+                    details: Vec::new(),
+                },
+                ReportSection {
+                    name: "Labels",
+                    count: Some(2),
+                    details: vec!["- add `bug`".to_string(), "- remove `old`".to_string()],
+                },
+            ],
+            blocked: vec![BlockedLabel {
+                label: "wontfix".to_string(),
+                usage: vec![
+                    LabelUsageEntry {
+                        number: 7,
+                        url: Some("https://github.com/acme/api/issues/7".to_string()),
+                        is_pr: false,
+                    },
+                    LabelUsageEntry {
+                        number: 0,
+                        url: None,
+                        is_pr: false,
+                    },
+                ],
+            }],
+            pr_note: "no PR (no .github file changes)".to_string(),
+            codeowners: None,
+        };
+        let md = render_markdown("acme", &OrgReport::default(), &[report]);
+        assert!(md.contains("| Repository | Settings | Labels | Blocked |"));
+        assert!(md.contains("| `api` | – | 2 | 1 |"));
+        assert!(
+            md.contains("<summary><code>api</code>: 2 changes, 1 blocked label removal</summary>")
+        );
+        assert!(md.contains("**Labels**\n\n- add `bug`\n- remove `old`\n"));
+        assert!(!md.contains("**Settings**"));
+        assert!(md.contains(
+            "- `wontfix` is still used by [issue #7](https://github.com/acme/api/issues/7) and more"
+        ));
+    }
+
+    fn compliance_report() -> RepoReport {
+        let section = |name, count| ReportSection {
+            name,
+            count,
+            details: vec!["- allow_squash_merge: false -> true".to_string()],
+        };
+        RepoReport {
+            repo: "api".to_string(),
+            sections: vec![
+                section("Settings", Some(1)),
+                section("Protection", Some(0)),
+                ReportSection {
+                    name: "Labels",
+                    count: Some(0),
+                    details: Vec::new(),
+                },
+            ],
+            blocked: Vec::new(),
+            pr_note: String::new(),
+            codeowners: Some(false),
+        }
+    }
+
+    #[test]
+    fn junit_has_a_case_per_policy() {
+        let xml = render_junit("acme", &OrgReport::default(), &[compliance_report()]);
+        assert!(xml.contains("tests=\"4\" failures=\"2\" skipped=\"0\""));
+        assert!(xml.contains("<testcase classname=\"acme/api\" name=\"labels in sync\"/>"));
+        assert!(xml.contains(
+            "<failure message=\"1 pending change\">- allow_squash_merge: false -&gt; true</failure>"
+        ));
+        assert!(xml.contains("name=\"CODEOWNERS present\">\n      <failure"));
+    }
+
+    #[test]
+    fn org_results_get_their_own_suite() {
+        let org_report = OrgReport {
+            sections: vec![ReportSection {
+                name: "Teams",
+                count: Some(1),
+                details: vec!["- create team `platform`".to_string()],
+            }],
+        };
+        let xml = render_junit("acme", &org_report, &[]);
+        assert!(xml.contains("<testsuite name=\"organization\" tests=\"1\" failures=\"1\""));
+        assert!(xml.contains("<testcase classname=\"acme\" name=\"teams in sync\">"));
+        let md = render_markdown("acme", &org_report, &[]);
+        assert!(md.contains("**Teams:** 1 change\n- create team `platform`\n"));
+    }
+
+    #[test]
+    fn sarif_reports_only_failures() {
+        let sarif: serde_json::Value = serde_json::from_str(&render_sarif(
+            "acme",
+            "gh-governor-conf.toml",
+            &OrgReport::default(),
+            &[compliance_report()],
+        ))
+        .unwrap();
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        let rules: Vec<_> = results
+            .iter()
+            .map(|r| r["ruleId"].as_str().unwrap())
+            .collect();
+        assert_eq!(rules, vec!["settings-in-sync", "codeowners-present"]);
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "gh-governor-conf.toml"
+        );
+    }
+}
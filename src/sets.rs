@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -6,7 +6,11 @@ use glob::glob;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, Result};
-use crate::settings::{BranchProtectionConfig, RepoSettings};
+use crate::forms::{is_issue_template, validate_issue_template};
+use crate::settings::{
+    AccessConfig, AutolinksConfig, BranchProtectionConfig, CustomProperties, DeliveryConfig,
+    DiscussionsConfig, EnvironmentsConfig, FileDelivery, ManageMode, RepoSettings, WebhooksConfig,
+};
 use crate::util::{SUPPORTED_EXTS, parse_by_extension};
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -18,6 +22,32 @@ pub struct LabelSpec {
     pub description: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct MilestoneSpec {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Due date as `YYYY-MM-DD`.
+    #[serde(default)]
+    pub due_on: Option<String>,
+    #[serde(default)]
+    pub state: Option<MilestoneState>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MilestonesConfig {
+    /// `exclusive` deletes undeclared milestones without open issues; defaults to `additive`.
+    pub mode: Option<ManageMode>,
+    pub milestones: Vec<MilestoneSpec>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MilestoneState {
+    Open,
+    Closed,
+}
+
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
 pub struct IssueTemplateFile {
     pub path: String,
@@ -57,8 +87,18 @@ pub struct SetDefinition {
     pub path: PathBuf,
     pub labels: Vec<LabelSpec>,
     pub issue_templates: Vec<IssueTemplateFile>,
+    /// Labels referenced by each issue template, keyed by template path.
+    pub template_labels: BTreeMap<String, Vec<String>>,
     pub repo_settings: Option<RepoSettings>,
     pub checks: Option<ChecksConfig>,
+    pub access: Option<AccessConfig>,
+    pub webhooks: Option<WebhooksConfig>,
+    pub environments: Option<EnvironmentsConfig>,
+    pub autolinks: Option<AutolinksConfig>,
+    pub properties: Option<CustomProperties>,
+    pub milestones: Option<MilestonesConfig>,
+    pub discussions: Option<DiscussionsConfig>,
+    pub file_delivery: Option<FileDelivery>,
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq, Default)]
@@ -79,6 +119,43 @@ where
     Ok(labels_from_map(map))
 }
 
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq, Default)]
+pub struct MilestoneFields {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub due_on: Option<String>,
+    #[serde(default)]
+    pub state: Option<MilestoneState>,
+}
+
+/// A milestones file is either a plain title map or a `mode` plus a `milestones` title map.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MilestonesFile {
+    Managed {
+        #[serde(default)]
+        mode: Option<ManageMode>,
+        milestones: HashMap<String, MilestoneFields>,
+    },
+    Titles(HashMap<String, MilestoneFields>),
+}
+
+impl From<MilestonesFile> for MilestonesConfig {
+    fn from(file: MilestonesFile) -> Self {
+        match file {
+            MilestonesFile::Managed { mode, milestones } => MilestonesConfig {
+                mode,
+                milestones: milestones_from_map(milestones),
+            },
+            MilestonesFile::Titles(map) => MilestonesConfig {
+                mode: None,
+                milestones: milestones_from_map(map),
+            },
+        }
+    }
+}
+
 fn labels_from_map(map: HashMap<String, LabelFields>) -> Vec<LabelSpec> {
     let mut labels: Vec<_> = map
         .into_iter()
@@ -117,42 +194,98 @@ pub fn load_set(base_dir: &Path, name: &str) -> Result<SetDefinition> {
         }
     }
     let checks = load_named_file::<ChecksConfig>(&path, "checks")?;
+    let access = load_named_file::<AccessConfig>(&path, "access")?;
+    let webhooks = load_named_file::<WebhooksConfig>(&path, "webhooks")?;
+    let environments = load_named_file::<EnvironmentsConfig>(&path, "environments")?;
+    let autolinks = load_named_file::<AutolinksConfig>(&path, "autolinks")?;
+    let properties = load_named_file::<CustomProperties>(&path, "properties")?;
+    let milestones =
+        load_named_file::<MilestonesFile>(&path, "milestones")?.map(MilestonesConfig::from);
+    let discussions = load_named_file::<DiscussionsConfig>(&path, "discussions")?;
+    let delivery = load_named_file::<DeliveryConfig>(&path, "delivery")?;
     let issue_templates = load_issue_templates(&path)?;
+    let mut template_labels = BTreeMap::new();
+    for template in &issue_templates {
+        if is_issue_template(&template.path) {
+            let labels =
+                validate_issue_template(&template.path, &template.contents).map_err(|reason| {
+                    Error::InvalidIssueTemplate {
+                        set: name.to_string(),
+                        path: template.path.clone(),
+                        reason,
+                    }
+                })?;
+            template_labels.insert(template.path.clone(), labels);
+        }
+    }
 
     Ok(SetDefinition {
         name: name.to_string(),
         path,
         labels,
         issue_templates,
+        template_labels,
         repo_settings,
         checks,
+        access,
+        webhooks,
+        environments,
+        autolinks,
+        properties,
+        milestones,
+        discussions,
+        file_delivery: delivery.and_then(|d| d.file_delivery),
     })
 }
 
+/// `.github` directories whose YAML files are managed through the file pipeline.
+pub const TEMPLATE_DIRS: [&str; 2] = ["ISSUE_TEMPLATE", "DISCUSSION_TEMPLATE"];
+
 fn load_issue_templates(set_path: &Path) -> Result<Vec<IssueTemplateFile>> {
     let mut templates = Vec::new();
-    let template_dir = set_path.join(".github").join("ISSUE_TEMPLATE");
-    for ext in ["yml", "yaml"] {
-        let pattern = template_dir.join(format!("*.{ext}"));
-        for entry in glob(pattern.to_str().unwrap_or_default())? {
-            let path = entry.map_err(Error::GlobGlob)?;
-            let contents =
-                fs::read_to_string(&path).map_err(|e| Error::io_with_path(e, path.clone()))?;
-            let mut rel = path.to_string_lossy().to_string();
-            if let Some(idx) = rel.find(".github/") {
-                rel = rel[idx..].to_string();
-            } else if let Ok(stripped) = path.strip_prefix(set_path) {
-                rel = stripped.to_string_lossy().to_string();
+    for dir in TEMPLATE_DIRS {
+        let template_dir = set_path.join(".github").join(dir);
+        let exts: &[&str] = if dir == "ISSUE_TEMPLATE" {
+            &["yml", "yaml", "md"]
+        } else {
+            &["yml", "yaml"]
+        };
+        for ext in exts {
+            let pattern = template_dir.join(format!("*.{ext}"));
+            for entry in glob(pattern.to_str().unwrap_or_default())? {
+                let path = entry.map_err(Error::GlobGlob)?;
+                let contents =
+                    fs::read_to_string(&path).map_err(|e| Error::io_with_path(e, path.clone()))?;
+                let mut rel = path.to_string_lossy().to_string();
+                if let Some(idx) = rel.find(".github/") {
+                    rel = rel[idx..].to_string();
+                } else if let Ok(stripped) = path.strip_prefix(set_path) {
+                    rel = stripped.to_string_lossy().to_string();
+                }
+                templates.push(IssueTemplateFile {
+                    path: rel,
+                    contents,
+                });
             }
-            templates.push(IssueTemplateFile {
-                path: rel,
-                contents,
-            });
         }
     }
     Ok(templates)
 }
 
+fn milestones_from_map(map: HashMap<String, MilestoneFields>) -> Vec<MilestoneSpec> {
+    let mut milestones: Vec<_> = map
+        .into_iter()
+        .map(|(title, fields)| MilestoneSpec {
+            title,
+            description: fields.description,
+            due_on: fields.due_on,
+            state: fields.state,
+        })
+        .collect();
+    milestones.sort_by(|a, b| a.title.cmp(&b.title));
+    milestones
+}
+
 fn load_labels_file(dir: &Path) -> Result<Option<Vec<LabelSpec>>> {
     for ext in SUPPORTED_EXTS {
         let candidate = dir.join(format!("labels.{ext}"));
@@ -166,7 +299,10 @@ fn load_labels_file(dir: &Path) -> Result<Option<Vec<LabelSpec>>> {
     Ok(None)
 }
 
-fn load_named_file<T: for<'de> Deserialize<'de>>(dir: &Path, stem: &str) -> Result<Option<T>> {
+pub(crate) fn load_named_file<T: for<'de> Deserialize<'de>>(
+    dir: &Path,
+    stem: &str,
+) -> Result<Option<T>> {
     for ext in SUPPORTED_EXTS {
         let candidate = dir.join(format!("{stem}.{ext}"));
         if candidate.exists() {
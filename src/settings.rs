@@ -1,11 +1,19 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::sets::{LabelSpec, deserialize_label_map};
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
 pub struct RepoSettings {
     #[serde(default)]
     pub pull_requests: Option<PullRequestSettings>,
     #[serde(default)]
     pub branch_protection: Option<BranchProtectionConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actions: Option<ActionsSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security: Option<SecuritySettings>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -86,6 +94,28 @@ pub fn map_squash_option(
     }
 }
 
+/// Reverse of [`map_squash_option`]; returns `None` for combinations GitHub's UI cannot express.
+pub fn squash_option_from_parts(
+    title: Option<&SquashMergeCommitTitle>,
+    message: Option<&SquashMergeCommitMessage>,
+) -> Option<SquashMergeOption> {
+    match (title?, message?) {
+        (SquashMergeCommitTitle::CommitOrPrTitle, SquashMergeCommitMessage::CommitMessages) => {
+            Some(SquashMergeOption::DefaultMessage)
+        }
+        (SquashMergeCommitTitle::PrTitle, SquashMergeCommitMessage::Blank) => {
+            Some(SquashMergeOption::PullRequestTitle)
+        }
+        (SquashMergeCommitTitle::PrTitle, SquashMergeCommitMessage::CommitMessages) => {
+            Some(SquashMergeOption::PullRequestTitleAndCommitDetails)
+        }
+        (SquashMergeCommitTitle::PrTitle, SquashMergeCommitMessage::PrBody) => {
+            Some(SquashMergeOption::PullRequestTitleAndDescription)
+        }
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum MergeCommitMessageOption {
@@ -113,6 +143,296 @@ pub fn map_merge_message_option(
     }
 }
 
+/// Reverse of [`map_merge_message_option`]; returns `None` for combinations GitHub's UI cannot express.
+pub fn merge_message_option_from_parts(
+    title: Option<&MergeCommitTitle>,
+    message: Option<&MergeCommitMessage>,
+) -> Option<MergeCommitMessageOption> {
+    match (title?, message?) {
+        (MergeCommitTitle::MergeMessage, MergeCommitMessage::PrTitle) => {
+            Some(MergeCommitMessageOption::DefaultMessage)
+        }
+        (MergeCommitTitle::PrTitle, MergeCommitMessage::PrTitle) => {
+            Some(MergeCommitMessageOption::PullRequestTitle)
+        }
+        (MergeCommitTitle::PrTitle, MergeCommitMessage::PrBody) => {
+            Some(MergeCommitMessageOption::PullRequestTitleAndDescription)
+        }
+        _ => None,
+    }
+}
+
+/// GitHub Actions policy for a repository.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct ActionsSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_actions: Option<AllowedActions>,
+    /// Only honoured by GitHub when `allowed_actions` is `selected`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_actions: Option<SelectedActions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_workflow_permissions: Option<WorkflowPermissions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub can_approve_pull_request_reviews: Option<bool>,
+    /// Which fork pull request contributors need approval before workflows run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fork_pr_approval: Option<ForkPrApprovalPolicy>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AllowedActions {
+    All,
+    LocalOnly,
+    Selected,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct SelectedActions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_owned_allowed: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_allowed: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patterns_allowed: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowPermissions {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ForkPrApprovalPolicy {
+    FirstTimeContributorsNewToGithub,
+    FirstTimeContributors,
+    AllExternalContributors,
+}
+
+/// Security and analysis features. When read from GitHub, `None` means the feature is not
+/// offered for the repository (e.g. advanced security without a GHAS license).
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct SecuritySettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub advanced_security: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_scanning: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_scanning_push_protection: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependabot_security_updates: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vulnerability_alerts: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub automated_security_fixes: Option<bool>,
+}
+
+/// Team and outside collaborator permissions, read from `access.{toml,yml,yaml,json}`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct AccessConfig {
+    /// `exclusive` removes teams/collaborators not listed; defaults to `additive`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ManageMode>,
+    /// Team slug -> permission.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub teams: BTreeMap<String, Permission>,
+    /// Outside collaborator login -> permission.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub collaborators: BTreeMap<String, Permission>,
+}
+
+/// Whether resources not declared in config are left alone or removed.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ManageMode {
+    #[default]
+    Additive,
+    Exclusive,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Pull,
+    Triage,
+    Push,
+    Maintain,
+    Admin,
+}
+
+impl Permission {
+    /// Parses both the REST permission names and the role names shown in the UI.
+    pub fn from_api(value: &str) -> Option<Self> {
+        match value {
+            "pull" | "read" => Some(Permission::Pull),
+            "triage" => Some(Permission::Triage),
+            "push" | "write" => Some(Permission::Push),
+            "maintain" => Some(Permission::Maintain),
+            "admin" => Some(Permission::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::Pull => "pull",
+            Permission::Triage => "triage",
+            Permission::Push => "push",
+            Permission::Maintain => "maintain",
+            Permission::Admin => "admin",
+        }
+    }
+}
+
+/// Repository webhooks, read from `webhooks.{toml,yml,yaml,json}`. Hooks are matched by URL.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct WebhooksConfig {
+    /// `exclusive` deletes hooks whose URL is not declared; defaults to `additive`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ManageMode>,
+    #[serde(default)]
+    pub hooks: Vec<WebhookSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct WebhookSpec {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<WebhookContentType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insecure_ssl: Option<bool>,
+    /// Name of the environment variable holding the secret; GitHub never returns secrets,
+    /// so secret drift cannot be detected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_env: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookContentType {
+    Json,
+    Form,
+}
+
+/// Deployment environments, read from `environments.{toml,yml,yaml,json}`. Environments not
+/// listed are left untouched.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct EnvironmentsConfig {
+    #[serde(default)]
+    pub environments: Vec<EnvironmentSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct EnvironmentSpec {
+    pub name: String,
+    /// Minutes to wait before a deployment may proceed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_timer: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewers: Option<EnvironmentReviewers>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prevent_self_review: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub can_admins_bypass: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment_branch_policy: Option<DeploymentBranchPolicy>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct EnvironmentReviewers {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub users: Vec<String>,
+    /// Team slugs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub teams: Vec<String>,
+}
+
+/// Either restrict deployments to protected branches, or to branches matching `custom_branches`;
+/// with neither set, any branch may deploy.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct DeploymentBranchPolicy {
+    #[serde(default)]
+    pub protected_branches: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_branches: Vec<String>,
+}
+
+impl DeploymentBranchPolicy {
+    /// Neither restriction is set: deployments are allowed from any branch, which GitHub
+    /// represents as having no policy.
+    pub fn allows_any_branch(&self) -> bool {
+        !self.protected_branches && self.custom_branches.is_empty()
+    }
+}
+
+/// Value of an organization custom property: a single string or, for multi-select
+/// properties, a list of strings.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum PropertyValue {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl PropertyValue {
+    /// Whether `actual` satisfies this value when used as a selector: any listed value
+    /// present in `actual` is a match.
+    pub fn matches(&self, actual: &PropertyValue) -> bool {
+        let wanted = self.values();
+        actual.values().iter().any(|v| wanted.contains(v))
+    }
+
+    pub fn values(&self) -> Vec<&str> {
+        match self {
+            PropertyValue::Single(v) => vec![v.as_str()],
+            PropertyValue::Multiple(vs) => vs.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PropertyValue::Single(v) => write!(f, "{v}"),
+            PropertyValue::Multiple(vs) => write!(f, "[{}]", vs.join(", ")),
+        }
+    }
+}
+
+/// Custom property values, read from `properties.{toml,yml,yaml,json}` as a flat
+/// `name = value` map.
+pub type CustomProperties = BTreeMap<String, PropertyValue>;
+
+/// Autolink references, read from `autolinks.{toml,yml,yaml,json}`. Autolinks are matched
+/// by key prefix.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct AutolinksConfig {
+    /// `exclusive` deletes autolinks whose prefix is not declared; defaults to `additive`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ManageMode>,
+    #[serde(default)]
+    pub autolinks: Vec<AutolinkSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AutolinkSpec {
+    pub key_prefix: String,
+    /// Must contain `<num>`, e.g. `https://jira.example.com/browse/JIRA-<num>`.
+    pub url_template: String,
+    /// Defaults to `true` like on GitHub.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_alphanumeric: Option<bool>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
 pub struct BranchProtectionConfig {
     #[serde(default)]
@@ -191,3 +511,174 @@ pub struct BranchRestrictions {
     #[serde(default)]
     pub apps: Option<Vec<String>>,
 }
+
+/// GitHub Discussions, read from `discussions.{toml,yml,yaml,json}`. Categories are matched
+/// by name. GitHub has no API to create, edit or delete categories, so they are only checked:
+/// apply fails for a repository until its categories are changed by hand to match.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct DiscussionsConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// `exclusive` reports undeclared categories for removal; defaults to `additive`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ManageMode>,
+    #[serde(default)]
+    pub categories: Vec<DiscussionCategorySpec>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DiscussionCategorySpec {
+    pub name: String,
+    /// Emoji shortcode, e.g. `:speech_balloon:`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<DiscussionFormat>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscussionFormat {
+    Open,
+    Question,
+    Announcement,
+    Poll,
+}
+
+/// How `.github` file changes reach the default branch, read from
+/// `delivery.{toml,yml,yaml,json}` in a set.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct DeliveryConfig {
+    #[serde(default)]
+    pub file_delivery: Option<FileDelivery>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FileDelivery {
+    /// Commit to a `gh-governor/updates-*` branch and open a PR.
+    #[default]
+    Pr,
+    /// Commit straight to the default branch, falling back to a PR when that is rejected.
+    Direct,
+}
+
+/// Organization-wide settings, read from `org-settings.{toml,yml,yaml,json}` next to the
+/// root config.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq, Default)]
+pub struct OrgSettings {
+    #[serde(default)]
+    pub default_repository_permission: Option<OrgBasePermission>,
+    #[serde(default)]
+    pub members_can_create_repositories: Option<bool>,
+    #[serde(default)]
+    pub members_can_create_pages: Option<bool>,
+    #[serde(default)]
+    pub members_can_fork_private_repositories: Option<bool>,
+    /// Report-only: GitHub does not allow changing the requirement through the API.
+    #[serde(default)]
+    pub two_factor_requirement_enabled: Option<bool>,
+    /// Labels for newly created repositories. Report-only: there is no public API for them.
+    #[serde(default, deserialize_with = "deserialize_label_map")]
+    pub default_labels: Vec<LabelSpec>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrgBasePermission {
+    Read,
+    Write,
+    Admin,
+    None,
+}
+
+/// Organization team tree, read from `teams.{toml,yml,yaml,json}` next to the root config.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct TeamsConfig {
+    #[serde(default)]
+    pub teams: Vec<TeamSpec>,
+}
+
+/// A team and its members. The `maintainers` and `members` lists are authoritative: users
+/// not listed are removed from the team.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct TeamSpec {
+    pub name: String,
+    /// Previous team name (or slug); an existing team with that name is renamed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy: Option<TeamPrivacy>,
+    /// Name (or slug) of the parent team; teams without one are moved to the top level.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub maintainers: Vec<String>,
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamPrivacy {
+    Secret,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamRole {
+    Member,
+    Maintainer,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_message_option_round_trips() {
+        for opt in [
+            MergeCommitMessageOption::DefaultMessage,
+            MergeCommitMessageOption::PullRequestTitle,
+            MergeCommitMessageOption::PullRequestTitleAndDescription,
+        ] {
+            let (title, msg) = map_merge_message_option(&opt);
+            assert_eq!(
+                merge_message_option_from_parts(title.as_ref(), msg.as_ref()),
+                Some(opt)
+            );
+        }
+        assert_eq!(
+            merge_message_option_from_parts(
+                Some(&MergeCommitTitle::MergeMessage),
+                Some(&MergeCommitMessage::Blank)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn squash_option_round_trips() {
+        for opt in [
+            SquashMergeOption::DefaultMessage,
+            SquashMergeOption::PullRequestTitle,
+            SquashMergeOption::PullRequestTitleAndCommitDetails,
+            SquashMergeOption::PullRequestTitleAndDescription,
+        ] {
+            let (title, msg) = map_squash_option(&opt);
+            assert_eq!(
+                squash_option_from_parts(title.as_ref(), msg.as_ref()),
+                Some(opt)
+            );
+        }
+        assert_eq!(
+            squash_option_from_parts(Some(&SquashMergeCommitTitle::CommitOrPrTitle), None),
+            None
+        );
+    }
+}
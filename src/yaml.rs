@@ -0,0 +1,155 @@
+use serde_yaml::{Mapping, Value};
+
+/// Whether two YAML documents have the same content, so that formatting, comments and key
+/// order do not count as changes.
+pub fn yaml_equivalent(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    match (
+        serde_yaml::from_str::<Value>(a),
+        serde_yaml::from_str::<Value>(b),
+    ) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Applies the top-level keys of `generated` to `existing` while keeping everything else in
+/// `existing` (comments, other keys, ordering) as written. Keys whose value already matches
+/// are left untouched; missing keys are appended. Falls back to `generated` when `existing`
+/// is not a plain YAML mapping.
+pub fn update_top_level_keys(existing: &str, generated: &str) -> String {
+    let (Some(current), Some(wanted)) = (parse_mapping(existing), parse_mapping(generated)) else {
+        return generated.to_string();
+    };
+    if existing
+        .lines()
+        .any(|l| l.starts_with("---") || l.starts_with("..."))
+    {
+        return generated.to_string();
+    }
+
+    let mut lines: Vec<String> = existing.lines().map(|l| l.to_string()).collect();
+    for (key, value) in &wanted {
+        if current.get(key) == Some(value) {
+            continue;
+        }
+        let Some(key_name) = key.as_str() else {
+            return generated.to_string();
+        };
+        let mut single = Mapping::new();
+        single.insert(key.clone(), value.clone());
+        let Ok(block) = serde_yaml::to_string(&single) else {
+            return generated.to_string();
+        };
+        let block: Vec<String> = block.lines().map(|l| l.to_string()).collect();
+        match find_key_block(&lines, key_name) {
+            Some((start, end)) => {
+                lines.splice(start..end, block);
+            }
+            None => lines.extend(block),
+        }
+    }
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    match serde_yaml::from_str::<Value>(&updated) {
+        Ok(Value::Mapping(result))
+            if wanted.iter().all(|(k, v)| result.get(k) == Some(v))
+                && current
+                    .iter()
+                    .filter(|(k, _)| !wanted.contains_key(*k))
+                    .all(|(k, v)| result.get(k) == Some(v)) =>
+        {
+            updated
+        }
+        _ => generated.to_string(),
+    }
+}
+
+fn parse_mapping(contents: &str) -> Option<Mapping> {
+    match serde_yaml::from_str::<Value>(contents) {
+        Ok(Value::Mapping(map)) => Some(map),
+        _ => None,
+    }
+}
+
+/// Line range `[start, end)` of a top-level key and its value. Trailing blank lines and
+/// comments stay outside the range since they usually belong to the next key.
+fn find_key_block(lines: &[String], key: &str) -> Option<(usize, usize)> {
+    let is_key_line = |line: &str| {
+        let rest = line
+            .strip_prefix(key)
+            .or_else(|| line.strip_prefix(&format!("\"{key}\"")))
+            .or_else(|| line.strip_prefix(&format!("'{key}'")));
+        rest.is_some_and(|r| r.trim_start().starts_with(':'))
+    };
+    let start = lines.iter().position(|l| is_key_line(l))?;
+    let mut end = start + 1;
+    while end < lines.len() {
+        let line = &lines[end];
+        let top_level = !line.is_empty() && !line.starts_with(' ') && !line.starts_with('\t');
+        if top_level && !line.starts_with('-') {
+            break;
+        }
+        end += 1;
+    }
+    while end > start + 1 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXISTING: &str = "\
+# Managed partly by hand
+blank_issues_enabled: false # keep it strict
+
+contact_links:
+- name: Forum
+  url: https://forum.example.com
+  about: Ask here
+
+issue_templates:
+  - file: bug.yml
+";
+
+    #[test]
+    fn treats_reformatted_yaml_as_equal() {
+        let reformatted = "issue_templates: [{file: bug.yml}]\ncontact_links:\n  - {name: Forum, url: 'https://forum.example.com', about: Ask here}\nblank_issues_enabled: false\n";
+        assert!(yaml_equivalent(EXISTING, reformatted));
+        assert!(!yaml_equivalent(EXISTING, "blank_issues_enabled: true\n"));
+    }
+
+    #[test]
+    fn replaces_only_changed_keys() {
+        let generated = "\
+blank_issues_enabled: false
+contact_links:
+- name: Forum
+  url: https://forum.example.com
+  about: Ask here
+issue_templates:
+- file: bug.yml
+- file: feature.yml
+";
+        let updated = update_top_level_keys(EXISTING, generated);
+        assert!(updated.starts_with(
+            "# Managed partly by hand\nblank_issues_enabled: false # keep it strict\n"
+        ));
+        assert!(updated.contains("  about: Ask here\n\nissue_templates:\n"));
+        assert!(updated.contains("- file: feature.yml"));
+        assert!(yaml_equivalent(&updated, generated));
+    }
+
+    #[test]
+    fn keeps_unknown_keys_and_appends_missing_ones() {
+        let existing = "custom_key: 1\n";
+        let updated = update_top_level_keys(existing, "blank_issues_enabled: true\n");
+        assert_eq!(updated, "custom_key: 1\nblank_issues_enabled: true\n");
+    }
+}